@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+//! CRC32 integrity check over the whole slot map layout.
+//!
+//! The checksum covers every byte of the layout except the checksum field itself. Header,
+//! trailer and array fields are fed in their little-endian form (the same form as
+//! `to_portable`), so the checksum stays valid across `to_portable`/`from_portable`.
+
+use crate::{
+    CHECKSUM_OFFSET, element_size, for_each_field, layout_size, trailer_offset, try_validate,
+};
+
+/// CRC-32 (IEEE 802.3, reflected polynomial `0xEDB8_8320`) lookup table
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xEDB8_8320
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+struct Crc32(u32);
+
+impl Crc32 {
+    const fn new() -> Self {
+        Self(0xFFFF_FFFF)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = CRC32_TABLE[((self.0 ^ u32::from(byte)) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    const fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Compute the checksum of the layout at `base`, skipping the stored checksum field.
+unsafe fn compute(base: *const u8) -> u32 {
+    unsafe {
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+        let size = layout_size(capacity, element_size);
+        let bytes = core::slice::from_raw_parts(base, size);
+        let checksum_off = trailer_offset(capacity, element_size) + CHECKSUM_OFFSET;
+
+        let mut crc = Crc32::new();
+        let mut pos = 0;
+        for_each_field(capacity, element_size, |offset, width| {
+            // Opaque bytes (values, padding) between the previous field and this one
+            crc.update(&bytes[pos..offset]);
+            pos = offset + width;
+
+            if offset == checksum_off {
+                return;
+            }
+            if width == 2 {
+                let value = *base.add(offset).cast::<u16>();
+                crc.update(&value.to_le_bytes());
+            } else {
+                let value = *base.add(offset).cast::<u32>();
+                crc.update(&value.to_le_bytes());
+            }
+        });
+        crc.update(&bytes[pos..]);
+
+        crc.finish()
+    }
+}
+
+/// Recompute the checksum and store it in the trailer.
+///
+/// With the `checksum` feature enabled this is done automatically by every mutating
+/// function. Writes made directly through value pointers (for example from
+/// `get_value_ptr`) are never tracked, so call this after such writes.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn update_checksum(base: *mut u8) {
+    unsafe {
+        let checksum = compute(base);
+        let trailer_off = trailer_offset(*base.cast::<u16>(), element_size(base));
+        core::ptr::write(
+            base.add(trailer_off + CHECKSUM_OFFSET).cast::<u32>(),
+            checksum,
+        );
+    }
+}
+
+/// Check the stored checksum against the current contents of the layout.
+/// Returns false if any byte changed since the checksum was last updated.
+///
+/// The buffer is first checked with `try_validate`, so a corrupted header can not make the
+/// checksum pass read outside `buffer_len` bytes; such buffers are reported as failing.
+#[must_use]
+pub fn verify_checksum(base: *const u8, buffer_len: usize) -> bool {
+    if try_validate(base, buffer_len).is_err() {
+        return false;
+    }
+
+    unsafe {
+        let trailer_off = trailer_offset(*base.cast::<u16>(), element_size(base));
+        let stored = *base.add(trailer_off + CHECKSUM_OFFSET).cast::<u32>();
+        stored == compute(base)
+    }
+}
@@ -0,0 +1,223 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+use core::fmt;
+
+/// Errors reported by the fallible (`Result`-returning) parts of the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotMapError {
+    /// The buffer is smaller than the layout requires.
+    BufferTooSmall { required: usize, actual: usize },
+    /// The buffer does not satisfy the required alignment.
+    Misaligned { required: usize },
+    /// A capacity of 0 was requested or found in the header.
+    ZeroCapacity,
+    /// All IDs are in use.
+    Full,
+    /// The handle is stale or out of range.
+    InvalidHandle,
+    /// The element size does not match what the caller expected.
+    ElementSizeMismatch { expected: u32, actual: u32 },
+    /// The buffer contents are not a valid slot map.
+    Corrupt { reason: &'static str },
+    /// The buffer already holds an initialized slot map.
+    AlreadyInitialized,
+}
+
+impl fmt::Display for SlotMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferTooSmall { required, actual } => write!(
+                f,
+                "buffer too small: {actual} bytes, layout requires {required}"
+            ),
+            Self::Misaligned { required } => {
+                write!(f, "buffer is not {required}-byte aligned")
+            }
+            Self::ZeroCapacity => f.write_str("capacity must not be 0"),
+            Self::Full => f.write_str("slot map is full"),
+            Self::InvalidHandle => f.write_str("handle is not alive"),
+            Self::ElementSizeMismatch { expected, actual } => write!(
+                f,
+                "element_size mismatch: expected {expected}, found {actual}"
+            ),
+            Self::Corrupt { reason } => write!(f, "slot map is corrupt: {reason}"),
+            Self::AlreadyInitialized => f.write_str("buffer already holds an initialized slot map"),
+        }
+    }
+}
+
+impl core::error::Error for SlotMapError {}
+
+/// Reasons a buffer failed `try_validate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The base pointer is not 4-byte aligned.
+    BadAlignment,
+    /// The buffer is smaller than the header or the layout described by the header.
+    BufferTooSmall { required: usize, actual: usize },
+    /// The header capacity is 0.
+    ZeroCapacity,
+    /// The header len is larger than the capacity.
+    LenExceedsCapacity { len: u16, capacity: u16 },
+    /// The trailer does not start with the expected magic code.
+    BadTrailerMagic { found: u32 },
+    /// The element size in the trailer differs from the one in the header.
+    ElemSizeMismatch { header: u32, trailer: u32 },
+    /// The trailer `element_align` is neither 0 nor a power of two of at least 4.
+    BadElementAlign { element_align: u16 },
+    /// The values region at `base + 8` is not aligned to the trailer `element_align`.
+    ValuesMisaligned { element_align: u16 },
+    /// The free stack top is larger than the capacity.
+    FreeTopExceedsCapacity { free_top: u16, capacity: u16 },
+    /// `len + free_top != capacity`.
+    InvariantViolated {
+        len: u16,
+        free_top: u16,
+        capacity: u16,
+    },
+    /// `free_stack[slot]` holds an id that is not below the capacity.
+    FreeIdOutOfRange { slot: u16, id: u16 },
+    /// `free_stack[slot]` holds an id that is also occupied.
+    FreeIdOccupied { slot: u16, id: u16 },
+    /// `id_to_index[id]` is not below len.
+    IndexOutOfRange { id: u16, index: u16, len: u16 },
+    /// `id_to_index[id]` is `index`, but `index_to_id[index]` is `back`.
+    MappingMismatch { id: u16, index: u16, back: u16 },
+    /// The number of occupied ids in `id_to_index` differs from len.
+    OccupiedCountMismatch { occupied: u16, len: u16 },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadAlignment => f.write_str("base must be 4-byte aligned"),
+            Self::BufferTooSmall { required, actual } => write!(
+                f,
+                "buffer too small: {actual} bytes, layout requires {required}"
+            ),
+            Self::ZeroCapacity => f.write_str("capacity must not be 0"),
+            Self::LenExceedsCapacity { len, capacity } => {
+                write!(f, "len ({len}) exceeds capacity ({capacity})")
+            }
+            Self::BadTrailerMagic { found } => {
+                write!(f, "invalid trailer magic 0x{found:08X}")
+            }
+            Self::ElemSizeMismatch { header, trailer } => write!(
+                f,
+                "element_size mismatch: header has {header}, trailer has {trailer}"
+            ),
+            Self::BadElementAlign { element_align } => {
+                write!(f, "invalid element_align {element_align}")
+            }
+            Self::ValuesMisaligned { element_align } => write!(
+                f,
+                "values region is not {element_align}-byte aligned, wrong base pointer"
+            ),
+            Self::FreeTopExceedsCapacity { free_top, capacity } => {
+                write!(f, "free_top ({free_top}) exceeds capacity ({capacity})")
+            }
+            Self::InvariantViolated {
+                len,
+                free_top,
+                capacity,
+            } => write!(
+                f,
+                "invariant violated: len ({len}) + free_top ({free_top}) != capacity ({capacity})"
+            ),
+            Self::FreeIdOutOfRange { slot, id } => {
+                write!(f, "free_stack[{slot}] holds out of range id {id}")
+            }
+            Self::FreeIdOccupied { slot, id } => {
+                write!(f, "free_stack[{slot}] holds id {id}, which is occupied")
+            }
+            Self::IndexOutOfRange { id, index, len } => {
+                write!(f, "id_to_index[{id}] ({index}) is not below len ({len})")
+            }
+            Self::MappingMismatch { id, index, back } => write!(
+                f,
+                "id_to_index[{id}] is {index} but index_to_id[{index}] is {back}"
+            ),
+            Self::OccupiedCountMismatch { occupied, len } => {
+                write!(f, "{occupied} ids are occupied but len is {len}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ValidationError {}
+
+impl From<ValidationError> for SlotMapError {
+    fn from(error: ValidationError) -> Self {
+        match error {
+            ValidationError::BadAlignment => Self::Misaligned { required: 4 },
+            ValidationError::BufferTooSmall { required, actual } => {
+                Self::BufferTooSmall { required, actual }
+            }
+            ValidationError::ZeroCapacity => Self::ZeroCapacity,
+            ValidationError::LenExceedsCapacity { .. } => Self::Corrupt {
+                reason: "len exceeds capacity",
+            },
+            ValidationError::BadTrailerMagic { .. } => Self::Corrupt {
+                reason: "invalid trailer magic",
+            },
+            ValidationError::ElemSizeMismatch { .. } => Self::Corrupt {
+                reason: "trailer element_size does not match header",
+            },
+            ValidationError::BadElementAlign { .. } => Self::Corrupt {
+                reason: "invalid element_align",
+            },
+            ValidationError::ValuesMisaligned { element_align } => Self::Misaligned {
+                required: element_align as usize,
+            },
+            ValidationError::FreeTopExceedsCapacity { .. } => Self::Corrupt {
+                reason: "free_top exceeds capacity",
+            },
+            ValidationError::InvariantViolated { .. } => Self::Corrupt {
+                reason: "len + free_top does not equal capacity",
+            },
+            ValidationError::FreeIdOutOfRange { .. } => Self::Corrupt {
+                reason: "free stack holds an out of range id",
+            },
+            ValidationError::FreeIdOccupied { .. } => Self::Corrupt {
+                reason: "free stack holds an occupied id",
+            },
+            ValidationError::IndexOutOfRange { .. } => Self::Corrupt {
+                reason: "id_to_index points past len",
+            },
+            ValidationError::MappingMismatch { .. } => Self::Corrupt {
+                reason: "id_to_index and index_to_id disagree",
+            },
+            ValidationError::OccupiedCountMismatch { .. } => Self::Corrupt {
+                reason: "occupied id count does not equal len",
+            },
+        }
+    }
+}
+
+/// Reasons `init_checked` refused to initialize a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitError {
+    /// The buffer already holds a slot map with the same capacity and element size.
+    AlreadyInitialized,
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyInitialized => f.write_str("buffer already holds an initialized slot map"),
+        }
+    }
+}
+
+impl core::error::Error for InitError {}
+
+impl From<InitError> for SlotMapError {
+    fn from(error: InitError) -> Self {
+        match error {
+            InitError::AlreadyInitialized => Self::AlreadyInitialized,
+        }
+    }
+}
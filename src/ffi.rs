@@ -0,0 +1,97 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+//! C ABI facade over the raw `u16` API, enabled by the `ffi` feature.
+//!
+//! Every function is `extern "C"` and unmangled, with a `dsm_` prefix. Handles are passed
+//! as separate `id` and `generation` arguments, and results that are `Option` in the Rust
+//! API are returned as a `bool` success flag with the payload written through out-pointers.
+//! The safety requirements are the same as for the wrapped functions.
+
+/// See [`crate::layout_size`].
+#[unsafe(no_mangle)]
+pub const extern "C" fn dsm_layout_size(capacity: u16, element_size: u32) -> usize {
+    crate::layout_size(capacity, element_size)
+}
+
+/// See [`crate::init`].
+/// # Safety
+/// Same as [`crate::init`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dsm_init(base: *mut u8, capacity: u16, element_size: u32) {
+    unsafe { crate::init(base, capacity, element_size) }
+}
+
+/// Allocate a slot, writing its handle to `out_id` and `out_generation`.
+/// Returns false, leaving the out-pointers untouched, if the slot map is full.
+/// # Safety
+/// Same as [`crate::allocate`], and `out_id` and `out_generation` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dsm_allocate(
+    base: *mut u8,
+    out_id: *mut u16,
+    out_generation: *mut u16,
+) -> bool {
+    unsafe {
+        let Some((id, generation)) = crate::allocate(base) else {
+            return false;
+        };
+        out_id.write(id);
+        out_generation.write(generation);
+        true
+    }
+}
+
+/// See [`crate::remove`].
+/// # Safety
+/// Same as [`crate::remove`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dsm_remove(base: *mut u8, id: u16, generation: u16) -> bool {
+    unsafe { crate::remove(base, id, generation) }
+}
+
+/// See [`crate::insert`].
+/// # Safety
+/// Same as [`crate::insert`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dsm_insert(
+    base: *mut u8,
+    id: u16,
+    generation: u16,
+    src: *const u8,
+) -> bool {
+    unsafe { crate::insert(base, id, generation, src) }
+}
+
+/// See [`crate::is_alive_handle`].
+/// An out of range or free id is reported as not alive, so a bad id from C never panics.
+/// # Safety
+/// Same as [`crate::is_alive_handle`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dsm_is_alive(base: *mut u8, id: u16, generation: u16) -> bool {
+    unsafe { crate::is_alive_handle(base, crate::Handle::new(id, generation)) }
+}
+
+/// See [`crate::element_count`].
+/// # Safety
+/// Same as [`crate::element_count`].
+#[unsafe(no_mangle)]
+pub const unsafe extern "C" fn dsm_element_count(base: *const u8) -> u16 {
+    unsafe { crate::element_count(base) }
+}
+
+/// See [`crate::values_base_ptr`].
+/// # Safety
+/// Same as [`crate::values_base_ptr`].
+#[unsafe(no_mangle)]
+pub const unsafe extern "C" fn dsm_values_base_ptr(base: *mut u8) -> *mut u8 {
+    unsafe { crate::values_base_ptr(base) }
+}
+
+/// See [`crate::stride`].
+#[unsafe(no_mangle)]
+pub const extern "C" fn dsm_stride(element_size: u32) -> usize {
+    crate::stride(element_size)
+}
@@ -0,0 +1,61 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+/// Packed `(id, generation)` handle.
+///
+/// The packing is stable and can be stored or sent over the network as a plain `u32`:
+/// the id is in the low 16 bits and the generation in the high 16 bits.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Handle(u32);
+
+impl Handle {
+    /// The all-zero handle. It is never returned by `allocate`, since generation 0 is
+    /// never valid.
+    pub const NULL: Self = Self(0);
+
+    #[must_use]
+    pub const fn new(id: u16, generation: u16) -> Self {
+        Self(((generation as u32) << 16) | id as u32)
+    }
+
+    #[must_use]
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    #[must_use]
+    pub const fn to_bits(self) -> u32 {
+        self.0
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn id(self) -> u16 {
+        self.0 as u16
+    }
+
+    #[must_use]
+    pub const fn generation(self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+
+    #[must_use]
+    pub const fn is_null(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<(u16, u16)> for Handle {
+    fn from((id, generation): (u16, u16)) -> Self {
+        Self::new(id, generation)
+    }
+}
+
+impl From<Handle> for (u16, u16) {
+    fn from(handle: Handle) -> Self {
+        (handle.id(), handle.generation())
+    }
+}
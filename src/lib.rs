@@ -18,7 +18,8 @@
 //!   offset 8, size = capacity * element_size
 //!
 //! Trailer (4-byte aligned):
-//!   - Header (12B): magic (u32), elem_size (u32), free_top (u16), pad (u16)
+//!   - Header (16B): magic (u32, 'SVE2'), elem_size (u32), free_top (u16),
+//!     element_align (u16), checksum (u32)
 //!   - Arrays (each u16[capacity]):
 //!     * id_to_index: ID -> dense index (0xFFFF = invalid)
 //!     * index_to_id: dense index -> ID
@@ -43,6 +44,21 @@
 //!
 //! - `len + free_top == capacity` (all IDs are either in-use or on free stack)
 //! - Sentinel value for invalid entries: `0xFFFF`
+//!
+//! # Element Alignment
+//!
+//! Values start at offset 8 and are packed at `index * element_size`, so with a 4-byte
+//! aligned base every slot is aligned to at most 4 (or less, for odd element sizes).
+//! Layouts created with `init_aligned` round `element_size` up to the requested alignment and
+//! place the header so that `base + 8` is aligned, by starting `base` a few bytes into the
+//! buffer. The alignment is recorded in the trailer `element_align` field
+//! (0 for layouts created with `init`).
+//!
+//! # Zero-Size Elements
+//!
+//! `element_size` may be 0, which turns the slot map into a plain generational id allocator.
+//! The values region is then empty, value pointers all point at offset 8 and value copies
+//! are no-ops, while ids, generations and the free stack work as usual.
 
 #![no_std]
 // Since we are doing low level memory manipulation with raw pointers
@@ -50,15 +66,33 @@
 #![allow(clippy::cast_ptr_alignment)]
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
-use core::ptr;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::{fmt, ptr};
+
+mod checksum;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod handle;
+pub mod typed;
+pub mod wide;
+
+pub use checksum::{update_checksum, verify_checksum};
+pub use error::{InitError, SlotMapError, ValidationError};
+pub use handle::Handle;
 
 // Constants for the new layout
 pub const VEC_HEADER_MAGIC_CODE: u32 = 0xC001_C0DE;
-const SVEC_TRAILER_MAGIC: u32 = 0x5356_4543; // TODO: 'SVEC' historical Magic code, should probably be changed in the future
+// 'SVE2'. The historical 'SVEC' (0x5356_4543) marks the 12-byte trailer header layout, whose
+// arrays sit 4 bytes earlier, so those buffers must not validate against this layout.
+const SVEC_TRAILER_MAGIC: u32 = 0x5356_4532;
 const INVALID_U16: u16 = 0xFFFF;
 const HEADER_SIZE: usize = 8; // capacity(2) + len(2) + element_size(4)
 const VALUES_OFFSET: usize = HEADER_SIZE;
-const TRAILER_HEADER_SIZE: usize = 12;
+const TRAILER_HEADER_SIZE: usize = 16;
+const CHECKSUM_OFFSET: usize = 12; // within the trailer header
 
 /// Align to 4-byte boundary
 #[inline]
@@ -66,8 +100,33 @@ const fn align4(x: usize) -> usize {
     (x + 3) & !3
 }
 
+/// Next generation for a retired id. Generation 0 is never valid, so it is skipped on wrap.
+///
+/// After a wrap the id is back at generation 1, so a handle from its first use validates
+/// again. `remove_with_overflow` reports this through its `wrapped` flag.
+#[inline]
+const fn next_generation(generation: u16) -> u16 {
+    match generation.wrapping_add(1) {
+        0 => 1,
+        next => next,
+    }
+}
+
+/// Refresh the trailer checksum after a mutation when the `checksum` feature is enabled
+#[inline]
+#[allow(unused_variables)]
+fn refresh_checksum(base: *mut u8) {
+    #[cfg(feature = "checksum")]
+    unsafe {
+        update_checksum(base);
+    }
+}
+
 /// Validate slot map integrity in debug builds.
+///
 /// Checks magic code, `element_size` consistency, and reasonable values for capacity/len.
+/// Also cross-checks the free stack against `id_to_index` and `index_to_id` against
+/// `id_to_index`, which makes every call O(capacity) in debug builds.
 #[inline]
 #[allow(unused_variables)]
 pub fn debug_validate_slotmap(base: *const u8) {
@@ -88,10 +147,6 @@ pub fn debug_validate_slotmap(base: *const u8) {
             len <= capacity,
             "len ({len}) must not exceed capacity ({capacity}) - memory corruption or uninitialized slot map"
         );
-        debug_assert_ne!(
-            elem_size, 0,
-            "element_size must not be 0 (did you call init()?)"
-        );
         debug_assert!(
             elem_size <= 1024 * 1024,
             "element_size ({elem_size}) is unreasonably large - possible memory corruption"
@@ -114,6 +169,13 @@ pub fn debug_validate_slotmap(base: *const u8) {
             "element_size mismatch: header has {elem_size}, trailer has {trailer_elem_size} - memory corruption detected"
         );
 
+        // Check that values are placed according to the recorded element alignment
+        let element_align = *base.add(trailer_off + 10).cast::<u16>() as usize;
+        debug_assert!(
+            element_align == 0 || (base as usize + VALUES_OFFSET).is_multiple_of(element_align),
+            "values region is not {element_align}-byte aligned - use the base returned by init_aligned()"
+        );
+
         // Validate free_top
         let free_top = *base.add(trailer_off + 8).cast::<u16>();
         debug_assert!(
@@ -128,7 +190,201 @@ pub fn debug_validate_slotmap(base: *const u8) {
             "Invariant violated: len ({len}) + free_top ({free_top}) != capacity ({capacity})\n\
              This indicates memory corruption or a bug in the slot map implementation"
         );
+
+        // Cross-check the arrays: the free ids and the occupied ids must partition 0..capacity
+        let base_mut = base.cast_mut();
+        let id_to_idx_ptr = id_to_index_ptr(base_mut, capacity, elem_size);
+        let idx_to_id_ptr = index_to_id_ptr(base_mut, capacity, elem_size);
+        let free_stk_ptr = free_stack_ptr(base_mut, capacity, elem_size);
+
+        for slot in 0..free_top as usize {
+            let id = *free_stk_ptr.add(slot);
+            debug_assert!(
+                id < capacity,
+                "free_stack[{slot}] holds id {id}, which is out of range (capacity {capacity})"
+            );
+            debug_assert_eq!(
+                *id_to_idx_ptr.add(id as usize),
+                INVALID_U16,
+                "id {id} is on the free stack (slot {slot}) but is also occupied - memory corruption detected"
+            );
+        }
+
+        let mut occupied = 0u16;
+        for id in 0..capacity {
+            let index = *id_to_idx_ptr.add(id as usize);
+            if index == INVALID_U16 {
+                continue;
+            }
+            occupied += 1;
+            debug_assert!(
+                index < len,
+                "id_to_index[{id}] = {index} is not below len ({len}) - memory corruption detected"
+            );
+            let back = *idx_to_id_ptr.add(index as usize);
+            debug_assert_eq!(
+                back, id,
+                "id_to_index[{id}] = {index} but index_to_id[{index}] = {back} - memory corruption detected"
+            );
+        }
+        debug_assert_eq!(
+            occupied, len,
+            "{occupied} ids are occupied but len is {len} - memory corruption detected"
+        );
+    }
+}
+
+/// Check that `buffer_len` bytes at `base` can hold the slot map described by its header.
+///
+/// This is the cheap precondition check for attaching to an existing buffer, such as a
+/// shared-memory region: only the alignment and the header are read, and the trailer is
+/// never touched. Use `try_validate` for a full check of the contents.
+///
+/// # Errors
+/// Returns [`ValidationError::BadAlignment`] or [`ValidationError::BufferTooSmall`]. A
+/// header whose layout size overflows `usize` reports `required` as `usize::MAX`.
+/// # Safety
+/// `base` must be readable for `min(buffer_len, HEADER_SIZE)` bytes.
+pub unsafe fn attach(base: *mut u8, buffer_len: usize) -> Result<(), ValidationError> {
+    if (base as usize) & 3 != 0 {
+        return Err(ValidationError::BadAlignment);
+    }
+    if buffer_len < HEADER_SIZE {
+        return Err(ValidationError::BufferTooSmall {
+            required: HEADER_SIZE,
+            actual: buffer_len,
+        });
+    }
+
+    let (capacity, element_size) = unsafe { (*base.cast::<u16>(), *base.add(4).cast::<u32>()) };
+    let Some(required) = checked_layout_size(capacity, element_size) else {
+        return Err(ValidationError::BufferTooSmall {
+            required: usize::MAX,
+            actual: buffer_len,
+        });
+    };
+    if buffer_len < required {
+        return Err(ValidationError::BufferTooSmall {
+            required,
+            actual: buffer_len,
+        });
+    }
+
+    Ok(())
+}
+
+/// Bytes needed by the slot map at `base`, i.e. `layout_size` of its header's capacity and
+/// element size.
+/// # Safety
+/// `base` must be 4-byte aligned and point to at least `HEADER_SIZE` readable bytes.
+#[must_use]
+pub const unsafe fn required_len(base: *const u8) -> usize {
+    unsafe { layout_size(*base.cast::<u16>(), *base.add(4).cast::<u32>()) }
+}
+
+/// Validate a slot map without panicking, for buffers from untrusted sources.
+///
+/// Unlike `debug_validate_slotmap` this runs in release builds and reports the first problem
+/// found. Besides the header and trailer counters, the four arrays are cross-checked in
+/// O(capacity), so a buffer that passes can be handed to `allocate` and `remove` safely. `buffer_len` is checked against `layout_size(capacity, element_size)` (see
+/// `attach`) before the trailer is read, so a truncated or malicious buffer never causes a
+/// read outside `buffer_len` bytes.
+///
+/// # Errors
+/// Returns the first [`ValidationError`] encountered.
+pub fn try_validate(base: *const u8, buffer_len: usize) -> Result<(), ValidationError> {
+    unsafe { attach(base.cast_mut(), buffer_len)? };
+
+    unsafe {
+        let capacity = *base.cast::<u16>();
+        let len = *base.add(2).cast::<u16>();
+        let elem_size = *base.add(4).cast::<u32>();
+
+        if capacity == 0 {
+            return Err(ValidationError::ZeroCapacity);
+        }
+        if len > capacity {
+            return Err(ValidationError::LenExceedsCapacity { len, capacity });
+        }
+
+        let trailer_off = trailer_offset(capacity, elem_size);
+        let trailer_magic = *base.add(trailer_off).cast::<u32>();
+        if trailer_magic != SVEC_TRAILER_MAGIC {
+            return Err(ValidationError::BadTrailerMagic {
+                found: trailer_magic,
+            });
+        }
+
+        let trailer_elem_size = *base.add(trailer_off + 4).cast::<u32>();
+        if trailer_elem_size != elem_size {
+            return Err(ValidationError::ElemSizeMismatch {
+                header: elem_size,
+                trailer: trailer_elem_size,
+            });
+        }
+
+        // 0 is the default from init, anything else must match where base actually is
+        let element_align = *base.add(trailer_off + 10).cast::<u16>();
+        if element_align != 0 {
+            if !element_align.is_power_of_two() || (element_align as usize) < alignment() {
+                return Err(ValidationError::BadElementAlign { element_align });
+            }
+            if !(base as usize + VALUES_OFFSET).is_multiple_of(element_align as usize) {
+                return Err(ValidationError::ValuesMisaligned { element_align });
+            }
+        }
+
+        let free_top = *base.add(trailer_off + 8).cast::<u16>();
+        if free_top > capacity {
+            return Err(ValidationError::FreeTopExceedsCapacity { free_top, capacity });
+        }
+        if u32::from(len) + u32::from(free_top) != u32::from(capacity) {
+            return Err(ValidationError::InvariantViolated {
+                len,
+                free_top,
+                capacity,
+            });
+        }
+
+        // The arrays are used as indices by allocate/remove, so they must be consistent
+        // before the buffer is trusted: free ids are in range and unoccupied, and every
+        // occupied id round-trips through index_to_id at an index below len
+        let base_mut = base.cast_mut();
+        let id_to_idx_ptr = id_to_index_ptr(base_mut, capacity, elem_size);
+        let idx_to_id_ptr = index_to_id_ptr(base_mut, capacity, elem_size);
+        let free_stk_ptr = free_stack_ptr(base_mut, capacity, elem_size);
+
+        for slot in 0..free_top {
+            let id = *free_stk_ptr.add(slot as usize);
+            if id >= capacity {
+                return Err(ValidationError::FreeIdOutOfRange { slot, id });
+            }
+            if *id_to_idx_ptr.add(id as usize) != INVALID_U16 {
+                return Err(ValidationError::FreeIdOccupied { slot, id });
+            }
+        }
+
+        let mut occupied = 0u16;
+        for id in 0..capacity {
+            let index = *id_to_idx_ptr.add(id as usize);
+            if index == INVALID_U16 {
+                continue;
+            }
+            occupied += 1;
+            if index >= len {
+                return Err(ValidationError::IndexOutOfRange { id, index, len });
+            }
+            let back = *idx_to_id_ptr.add(index as usize);
+            if back != id {
+                return Err(ValidationError::MappingMismatch { id, index, back });
+            }
+        }
+        if occupied != len {
+            return Err(ValidationError::OccupiedCountMismatch { occupied, len });
+        }
     }
+
+    Ok(())
 }
 
 /// Compute trailer offset (after dense values, 4-byte aligned)
@@ -153,12 +409,192 @@ pub const fn layout_size(capacity: u16, element_size: u32) -> usize {
     arrays_off + arrays_size
 }
 
+/// Same as `layout_size`, but `None` if the size does not fit in `usize`.
+///
+/// Used where `capacity` and `element_size` come from an untrusted header, since on 32-bit
+/// targets `capacity * element_size` can wrap.
+const fn checked_layout_size(capacity: u16, element_size: u32) -> Option<usize> {
+    let Some(values_size) = (capacity as usize).checked_mul(element_size as usize) else {
+        return None;
+    };
+    let Some(values_end) = values_size.checked_add(VALUES_OFFSET + 3) else {
+        return None;
+    };
+    let Some(trailer_end) = (values_end & !3).checked_add(TRAILER_HEADER_SIZE + 3) else {
+        return None;
+    };
+    (trailer_end & !3).checked_add(4 * capacity as usize * size_of::<u16>())
+}
+
+/// Return `n` if a buffer of `n` bytes can hold `layout_size(capacity, element_size)`.
+///
+/// Meant for const contexts, where the panic becomes a compile error:
+/// `const _: usize = assert_layout_fits(N, CAPACITY, ELEMENT_SIZE);`
+/// # Panics
+/// If `n` is less than `layout_size(capacity, element_size)`.
+#[must_use]
+pub const fn assert_layout_fits(n: usize, capacity: u16, element_size: u32) -> usize {
+    assert!(
+        n >= layout_size(capacity, element_size),
+        "buffer is smaller than layout_size(capacity, element_size)"
+    );
+    n
+}
+
+/// Largest capacity whose `layout_size(capacity, element_size)` fits in `budget_bytes`,
+/// capped at `u16::MAX`. Returns 0 if not even a capacity of 1 fits.
+///
+/// The alignment padding makes `layout_size` awkward to invert directly, so this is an exact
+/// binary search over it.
+#[must_use]
+pub const fn capacity_for_budget(budget_bytes: usize, element_size: u32) -> u16 {
+    // Invariant: layout_size(low) fits (or low == 0), layout_size(high + 1) does not
+    let mut low = 0u32;
+    let mut high = u16::MAX as u32;
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        #[allow(clippy::cast_possible_truncation)]
+        let fits = layout_size(mid as u16, element_size) <= budget_bytes;
+        if fits {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let capacity = low as u16;
+    capacity
+}
+
+/// Byte offsets from `base` of each region of a slot map, see `layout_offsets`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutOffsets {
+    /// Start of the dense values, `element_size` bytes each
+    pub values_offset: usize,
+    /// Start of the trailer header (magic, element size, `free_top`, align, checksum)
+    pub trailer_offset: usize,
+    /// Start of the four `u16[capacity]` arrays, the same as `id_to_index`
+    pub arrays_offset: usize,
+    pub id_to_index: usize,
+    pub index_to_id: usize,
+    pub generation: usize,
+    pub free_stack: usize,
+}
+
+/// Compute the offset of every region of a slot map, for code generators that emit their
+/// own accessors. The end of the last array is `layout_size(capacity, element_size)`.
+#[must_use]
+pub const fn layout_offsets(capacity: u16, element_size: u32) -> LayoutOffsets {
+    let array_size = capacity as usize * size_of::<u16>();
+    let trailer_off = trailer_offset(capacity, element_size);
+    let arrays_off = align4(trailer_off + TRAILER_HEADER_SIZE);
+
+    LayoutOffsets {
+        values_offset: VALUES_OFFSET,
+        trailer_offset: trailer_off,
+        arrays_offset: arrays_off,
+        id_to_index: arrays_off,
+        index_to_id: arrays_off + array_size,
+        generation: arrays_off + 2 * array_size,
+        free_stack: arrays_off + 3 * array_size,
+    }
+}
+
+/// Snapshot of a slot map's header fields and region offsets, see `describe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotmapDescription {
+    pub capacity: u16,
+    pub len: u16,
+    pub element_size: u32,
+    pub free_top: u16,
+    /// Byte offsets from `base` of the values, trailer and the four arrays
+    pub offsets: LayoutOffsets,
+}
+
+/// Describe the slot map at `base` for inspection tools: header fields, `free_top` and the
+/// offset of every region.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn describe(base: *const u8) -> SlotmapDescription {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = capacity(base);
+        let element_size = element_size(base);
+
+        SlotmapDescription {
+            capacity,
+            len: element_count(base),
+            element_size,
+            free_top: free_count(base),
+            offsets: layout_offsets(capacity, element_size),
+        }
+    }
+}
+
+/// Round `element_size` up to a multiple of `element_align`, giving the per-element stride
+/// of an aligned layout.
+#[must_use]
+pub const fn aligned_element_size(element_size: u32, element_align: u16) -> u32 {
+    let align = element_align as u32;
+    element_size.div_ceil(align) * align
+}
+
+/// Bytes between the start of the buffer and `base` in an aligned layout, so that the values
+/// region (`base + 8`) lands on an `element_align` boundary.
+const fn aligned_base_offset(element_align: u16) -> usize {
+    let align = element_align as usize;
+    HEADER_SIZE.div_ceil(align) * align - HEADER_SIZE
+}
+
+/// Compute total bytes needed for a slot map whose elements require `element_align`
+/// alignment. See `init_aligned`.
+///
+/// `element_align` must be a power of two. The buffer itself must be aligned to
+/// `max(element_align, alignment())`.
+#[must_use]
+pub const fn layout_size_aligned(capacity: u16, element_size: u32, element_align: u16) -> usize {
+    aligned_base_offset(element_align)
+        + layout_size(capacity, aligned_element_size(element_size, element_align))
+}
+
 /// Alignment requirement for the sparse vector.
 #[must_use]
 pub const fn alignment() -> usize {
     4
 }
 
+/// Memory layout for a slot map, `layout_size(capacity, element_size)` bytes aligned to
+/// `alignment()`, ready for `core::alloc::GlobalAlloc`.
+/// # Panics
+/// If the size overflows `isize` when rounded up to the alignment, only possible on 32-bit
+/// or smaller targets.
+#[must_use]
+pub const fn mem_layout(capacity: u16, element_size: u32) -> core::alloc::Layout {
+    match core::alloc::Layout::from_size_align(layout_size(capacity, element_size), alignment()) {
+        Ok(layout) => layout,
+        Err(_) => panic!("layout_size overflows isize"),
+    }
+}
+
+/// Allocate a zeroed buffer and `init` a slot map in it.
+///
+/// The buffer is returned as `u32` words so that it is 4-byte aligned and can be used as a
+/// slot map directly (`boxed.as_mut_ptr().cast::<u8>()`).
+/// # Panics
+/// In debug builds, if `capacity` is 0 (see `init`).
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn boxed(capacity: u16, element_size: u32) -> alloc::boxed::Box<[u32]> {
+    let size = layout_size(capacity, element_size);
+    let mut words = alloc::vec![0u32; size.div_ceil(4)].into_boxed_slice();
+    unsafe {
+        init(words.as_mut_ptr().cast::<u8>(), capacity, element_size);
+    }
+    words
+}
+
 /// Helper functions to get pointers to trailer arrays
 #[inline]
 const unsafe fn id_to_index_ptr(base: *mut u8, capacity: u16, element_size: u32) -> *mut u16 {
@@ -234,7 +670,8 @@ pub unsafe fn init(base: *mut u8, capacity: u16, element_size: u32) {
         ptr::write(base.add(trailer_off).cast::<u32>(), SVEC_TRAILER_MAGIC);
         ptr::write(base.add(trailer_off + 4).cast::<u32>(), element_size);
         ptr::write(base.add(trailer_off + 8).cast::<u16>(), capacity); // free_top = capacity
-        ptr::write(base.add(trailer_off + 10).cast::<u16>(), 0); // _pad2
+        ptr::write(base.add(trailer_off + 10).cast::<u16>(), 0); // element_align, 0 = default
+        ptr::write(base.add(trailer_off + CHECKSUM_OFFSET).cast::<u32>(), 0);
 
         // Initialize id_to_index array (all invalid)
         let id_to_idx_ptr = id_to_index_ptr(base, capacity, element_size);
@@ -261,6 +698,96 @@ pub unsafe fn init(base: *mut u8, capacity: u16, element_size: u32) {
         for i in 0..cap {
             ptr::write(free_stk_ptr.add(i), i as u16);
         }
+
+        refresh_checksum(base);
+    }
+}
+
+/// Initialize like `init`, but refuse to wipe a buffer that already holds a slot map with
+/// the same `capacity` and `element_size`.
+///
+/// The header and the trailer magic are checked first. A zeroed buffer never matches, since
+/// its header capacity is 0. Use `init` to force a reinitialization.
+/// # Errors
+/// Returns [`InitError::AlreadyInitialized`] if the buffer is already initialized.
+/// # Safety
+/// Same requirements as `init`.
+pub unsafe fn init_checked(
+    base: *mut u8,
+    capacity: u16,
+    element_size: u32,
+) -> Result<(), InitError> {
+    unsafe {
+        debug_assert_eq!((base as usize) & 3, 0, "base must be 4-byte aligned");
+
+        let trailer_off = trailer_offset(capacity, element_size);
+        if *base.cast::<u16>() == capacity
+            && *base.add(4).cast::<u32>() == element_size
+            && *base.add(trailer_off).cast::<u32>() == SVEC_TRAILER_MAGIC
+        {
+            return Err(InitError::AlreadyInitialized);
+        }
+
+        init(base, capacity, element_size);
+
+        Ok(())
+    }
+}
+
+/// Initialize a slot map with every dense slot aligned to `element_align`.
+///
+/// The element size is rounded up to a multiple of `element_align` (see
+/// `aligned_element_size`), and the header is placed so that the values region starts on an
+/// `element_align` boundary. Returns the `base` pointer to use with all other functions,
+/// which may be a few bytes into `buffer` for alignments above 8.
+///
+/// # Safety
+/// - `buffer` must point to valid memory of at least
+///   `layout_size_aligned(capacity, element_size, element_align)` bytes
+/// - `buffer` must be aligned to `max(element_align, alignment())`
+/// - `element_align` must be a power of two
+/// - `capacity` must not be 0
+pub unsafe fn init_aligned(
+    buffer: *mut u8,
+    capacity: u16,
+    element_size: u32,
+    element_align: u16,
+) -> *mut u8 {
+    unsafe {
+        debug_assert!(
+            element_align.is_power_of_two(),
+            "element_align ({element_align}) must be a power of two"
+        );
+        debug_assert_eq!(
+            (buffer as usize) % (element_align as usize).max(alignment()),
+            0,
+            "buffer must be aligned to element_align"
+        );
+
+        let base = buffer.add(aligned_base_offset(element_align));
+        let stride = aligned_element_size(element_size, element_align);
+        init(base, capacity, stride);
+
+        // Every base is at least 4-byte aligned, so smaller alignments are recorded as 4
+        #[allow(clippy::cast_possible_truncation)]
+        let recorded_align = element_align.max(alignment() as u16);
+        let trailer_off = trailer_offset(capacity, stride);
+        ptr::write(base.add(trailer_off + 10).cast::<u16>(), recorded_align);
+        refresh_checksum(base);
+
+        base
+    }
+}
+
+/// Get the element alignment recorded by `init_aligned` (0 for layouts created by `init`).
+/// Alignments below 4 are recorded as 4.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub const unsafe fn element_align(base: *const u8) -> u16 {
+    unsafe {
+        let trailer_off = trailer_offset(capacity(base), element_size(base));
+        *base.add(trailer_off + 10).cast::<u16>()
     }
 }
 
@@ -307,9 +834,74 @@ pub unsafe fn clear(base: *mut u8) {
         let gen_ptr = generation_ptr(base, capacity, element_size);
         for i in 0..capacity as usize {
             let old_gen = *gen_ptr.add(i);
-            ptr::write(gen_ptr.add(i), old_gen.wrapping_add(1));
+            ptr::write(gen_ptr.add(i), next_generation(old_gen));
+        }
+
+        refresh_checksum(base);
+    }
+}
+
+/// Clear the slot map like `clear`, and also restart every generation counter at 1.
+///
+/// The map ends up in the same state as right after `init`. This is opt-in because it
+/// breaks the handle guarantee: a handle issued before the reset can match a new
+/// allocation of the same id and will then be treated as alive.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn clear_reset_generations(base: *mut u8) {
+    unsafe {
+        clear(base);
+        compact_generations(base);
+    }
+}
+
+/// Reset the generation of every id, free or occupied, to 1, keeping all elements.
+///
+/// Meant for normalizing a map before saving it, as small uniform generations compress
+/// well. **This invalidates every outstanding handle, and worse, a stale handle with
+/// generation 1 becomes valid again for whatever now occupies its id.** Only call it when
+/// all handles held outside the map are discarded; read fresh handles afterwards, for
+/// example with `handle_for_index` or `iter`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn compact_generations(base: *mut u8) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+        let gen_ptr = generation_ptr(base, capacity, element_size);
+        for i in 0..capacity as usize {
+            ptr::write(gen_ptr.add(i), 1);
         }
+
+        refresh_checksum(base);
+    }
+}
+
+/// Reinitialize the buffer for a different element size, with as large a capacity as
+/// `buffer_len` bytes allow (see `capacity_for_budget`).
+///
+/// Everything in the map is wiped, since the whole layout changes, and the result is a
+/// default (unaligned) layout like one created by `init`. Returns the new capacity, or None,
+/// leaving the buffer untouched, if not even a capacity of 1 fits.
+/// # Safety
+/// `base` must be 4-byte aligned and valid for writes of `buffer_len` bytes.
+pub unsafe fn reinit_element_size(
+    base: *mut u8,
+    buffer_len: usize,
+    new_element_size: u32,
+) -> Option<u16> {
+    let capacity = capacity_for_budget(buffer_len, new_element_size);
+    if capacity == 0 {
+        return None;
+    }
+
+    unsafe {
+        init(base, capacity, new_element_size);
     }
+
+    Some(capacity)
 }
 
 /// Allocate a new ID and generation. Returns (id, generation) for the new handle.
@@ -359,63 +951,156 @@ pub unsafe fn allocate(base: *mut u8) -> Option<(u16, u16)> {
 
         //eprintln!("slotmap:{base:p} allocate id:{id} (index:{index}) gen:{generation}, len:{}", len + 1);
 
+        refresh_checksum(base);
+
         Some((id, generation))
     }
 }
 
-/// Compute offset of values region (always 8 in the new layout)
-#[must_use]
-pub const fn values_offset(_base: *const u8) -> usize {
-    VALUES_OFFSET
-}
-
-/// Validate handle and get dense index
-unsafe fn validate_handle(base: *mut u8, id: u16, generation: u16) -> Option<u16> {
+/// Allocate the specific id `wanted_id` instead of the one on top of the free stack.
+///
+/// Lets peers that must agree on ids (for example in lockstep networking) allocate the same
+/// id for the same entity, whatever order their earlier removals happened in. The id is taken
+/// out of the free stack by swapping it with the top entry, and appended to the dense array
+/// like `allocate` does. Returns the generation of the new handle, or None if `wanted_id` is
+/// out of range or already occupied.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn allocate_specific(base: *mut u8, wanted_id: u16) -> Option<u16> {
     unsafe {
-        let capacity = *base.cast::<u16>();
-        let element_size_val = element_size(base);
+        debug_validate_slotmap(base);
 
-        // Check id bounds
-        if id >= capacity {
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+        if wanted_id >= capacity {
             return None;
         }
 
-        // Check generation
-        let gen_ptr = generation_ptr(base, capacity, element_size_val);
-        if *gen_ptr.add(id as usize) != generation {
+        let id_to_idx_ptr = id_to_index_ptr(base, capacity, element_size);
+        if *id_to_idx_ptr.add(wanted_id as usize) != INVALID_U16 {
             return None;
         }
 
-        // Check id_to_index validity
-        let id_to_idx_ptr = id_to_index_ptr(base, capacity, element_size_val);
-        let index = *id_to_idx_ptr.add(id as usize);
-        if index == INVALID_U16 {
-            return None;
-        }
+        // A free id is always somewhere in free_stack[0..free_top]
+        let free_top_p = free_top_ptr(base, capacity, element_size);
+        let free_top = *free_top_p;
+        let free_stk_ptr = free_stack_ptr(base, capacity, element_size);
+        let free_stack = core::slice::from_raw_parts_mut(free_stk_ptr, free_top as usize);
+        let slot = free_stack.iter().position(|&id| id == wanted_id)?;
+        free_stack.swap(slot, free_top as usize - 1);
+        ptr::write(free_top_p, free_top - 1);
 
-        Some(index)
+        let len_ptr = base.add(2).cast::<u16>();
+        let index = *len_ptr;
+        ptr::write(len_ptr, index + 1);
+        ptr::write(id_to_idx_ptr.add(wanted_id as usize), index);
+        ptr::write(
+            index_to_id_ptr(base, capacity, element_size).add(index as usize),
+            wanted_id,
+        );
+
+        refresh_checksum(base);
+
+        Some(*generation_ptr(base, capacity, element_size).add(wanted_id as usize))
     }
 }
 
-/// Insert raw bytes at handle (id, generation).
-/// Uses dense indexing: validates handle, gets dense index, writes to values[index].
-/// # Safety
+/// Get the handle that the next `allocate` will return, without changing anything.
 ///
-#[inline]
-pub unsafe fn insert(base: *mut u8, id: u16, generation: u16, src: *const u8) -> bool {
+/// Returns None if the slot map is full. The prediction holds as long as no element is
+/// removed (or the map otherwise modified) before that `allocate`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn peek_next_handle(base: *const u8) -> Option<(u16, u16)> {
     unsafe {
         debug_validate_slotmap(base);
 
+        let base_mut = base.cast_mut();
+        let capacity = *base.cast::<u16>();
         let element_size = element_size(base);
 
-        // Validate handle and get dense index
-        let Some(index) = validate_handle(base, id, generation) else {
-            return false;
-        };
-
+        let free_top = *free_top_ptr(base_mut, capacity, element_size);
+        if free_top == 0 {
+            return None;
+        }
+
+        let id = *free_stack_ptr(base_mut, capacity, element_size).add(free_top as usize - 1);
+        let generation = *generation_ptr(base_mut, capacity, element_size).add(id as usize);
+
+        Some((id, generation))
+    }
+}
+
+/// Compute offset of values region (always 8 in the new layout)
+#[must_use]
+pub const fn values_offset(_base: *const u8) -> usize {
+    VALUES_OFFSET
+}
+
+/// Pointer to the start of the dense values region, `base + values_offset(base)`.
+/// Element `i` (for `i < len`) starts at `values_base_ptr(base) + i * stride(element_size)`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub const unsafe fn values_base_ptr(base: *mut u8) -> *mut u8 {
+    unsafe { base.add(VALUES_OFFSET) }
+}
+
+/// Distance in bytes between consecutive dense values.
+#[must_use]
+pub const fn stride(element_size: u32) -> usize {
+    element_size as usize
+}
+
+/// Validate handle and get dense index
+unsafe fn validate_handle(base: *mut u8, id: u16, generation: u16) -> Option<u16> {
+    unsafe {
+        let capacity = *base.cast::<u16>();
+        let element_size_val = element_size(base);
+
+        // Check id bounds
+        if id >= capacity {
+            return None;
+        }
+
+        // Check generation
+        let gen_ptr = generation_ptr(base, capacity, element_size_val);
+        if *gen_ptr.add(id as usize) != generation {
+            return None;
+        }
+
+        // Check id_to_index validity
+        let id_to_idx_ptr = id_to_index_ptr(base, capacity, element_size_val);
+        let index = *id_to_idx_ptr.add(id as usize);
+        if index == INVALID_U16 {
+            return None;
+        }
+
+        Some(index)
+    }
+}
+
+/// Insert raw bytes at handle (id, generation).
+/// Uses dense indexing: validates handle, gets dense index, writes to values[index].
+/// # Safety
+///
+#[inline]
+pub unsafe fn insert(base: *mut u8, id: u16, generation: u16, src: *const u8) -> bool {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let element_size = element_size(base);
+
+        // Validate handle and get dense index
+        let Some(index) = validate_handle(base, id, generation) else {
+            return false;
+        };
+
         // Write to values[index]
         let offset = VALUES_OFFSET + (index as usize) * (element_size as usize);
         ptr::copy_nonoverlapping(src, base.add(offset), element_size as usize);
+        refresh_checksum(base);
         true
     }
 }
@@ -424,22 +1109,82 @@ pub unsafe fn insert(base: *mut u8, id: u16, generation: u16, src: *const u8) ->
 /// # Safety
 ///
 pub unsafe fn remove(base: *mut u8, id: u16, generation: u16) -> bool {
+    unsafe { remove_with_overflow(base, id, generation).0 }
+}
+
+/// Remove by handle like `remove`, also reporting whether the retired id's generation wrapped.
+///
+/// Returns `(removed, wrapped)`. When `wrapped` is true the generation went past `u16::MAX`
+/// and restarted at 1. Generations are not unique across a wrap: once the id is allocated
+/// again, every old handle to it with generation 1 is alive again and resolves to the new
+/// element. Callers that need absolute safety can stop reusing the id (for example by
+/// keeping it allocated).
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn remove_with_overflow(base: *mut u8, id: u16, generation: u16) -> (bool, bool) {
     unsafe {
         debug_validate_slotmap(base);
 
-        let capacity = *base.cast::<u16>();
-        let element_size_val = element_size(base);
-
         // Validate handle and get dense index
         let Some(index) = validate_handle(base, id, generation) else {
-            return false;
+            return (false, false);
         };
 
+        // A handle that validates on an empty map means the mappings are corrupt, and
+        // remove_at_index would underflow `len - 1`
+        if element_count(base) == 0 {
+            return (false, false);
+        }
+
+        let wrapped = remove_at_index(base, index);
+
+        refresh_checksum(base);
+
+        (true, wrapped)
+    }
+}
+
+/// Invalidate every outstanding handle to an element while keeping the element itself.
+///
+/// The generation of `id` is bumped (skipping 0 on wrap) but the value and its dense
+/// position are left untouched. Returns the new generation, so the caller can rebind to
+/// `(id, new_generation)`, or None if the handle was not alive.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn invalidate(base: *mut u8, id: u16, generation: u16) -> Option<u16> {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        validate_handle(base, id, generation)?;
+
+        let capacity = *base.cast::<u16>();
+        let gen_ptr = generation_ptr(base, capacity, element_size(base));
+        let new_generation = next_generation(generation);
+        ptr::write(gen_ptr.add(id as usize), new_generation);
+
+        refresh_checksum(base);
+
+        Some(new_generation)
+    }
+}
+
+/// Swap-remove the element at dense `index` and retire its id.
+/// Returns true if the retired id's generation wrapped.
+/// The caller must guarantee `index < len`, and refresh the checksum afterwards.
+unsafe fn remove_at_index(base: *mut u8, index: u16) -> bool {
+    unsafe {
+        let capacity = *base.cast::<u16>();
+        let element_size_val = element_size(base);
+
         let len_ptr = base.add(2).cast::<u16>();
         let len = *len_ptr;
+        debug_assert!(index < len, "index ({index}) must be less than len ({len})");
         let last = len - 1;
 
-        //eprintln!("slotmap:{base:p} remove id:{id} (index:{index}) gen:{generation}");
+        let idx_to_id_ptr = index_to_id_ptr(base, capacity, element_size_val);
+        let id = *idx_to_id_ptr.add(index as usize);
+
+        //eprintln!("slotmap:{base:p} remove id:{id} (index:{index})");
 
         // If not removing the last element, swap with last
         if index != last {
@@ -456,7 +1201,6 @@ pub unsafe fn remove(base: *mut u8, id: u16, generation: u16) -> bool {
             );
 
             // Fix maps: moved_id is the id that was at last
-            let idx_to_id_ptr = index_to_id_ptr(base, capacity, element_size_val);
             let moved_id = *idx_to_id_ptr.add(last as usize);
 
             // Update index_to_id[index] = moved_id
@@ -468,7 +1212,6 @@ pub unsafe fn remove(base: *mut u8, id: u16, generation: u16) -> bool {
         }
 
         // Clear last slot maps
-        let idx_to_id_ptr = index_to_id_ptr(base, capacity, element_size_val);
         ptr::write(idx_to_id_ptr.add(last as usize), INVALID_U16);
 
         let id_to_idx_ptr = id_to_index_ptr(base, capacity, element_size_val);
@@ -476,12 +1219,22 @@ pub unsafe fn remove(base: *mut u8, id: u16, generation: u16) -> bool {
 
         // Decrement len
         ptr::write(len_ptr, last);
-        //eprintln!("slotmap:{base:p} remove id:{id} (index:{index}) gen:{generation} len:{last} written");
 
-        // Retire id: increment generation and push to free_stack
+        retire_id(base, id)
+    }
+}
+
+/// Bump the generation of a no longer used `id` and push it on the free stack.
+/// Returns true if the generation wrapped.
+unsafe fn retire_id(base: *mut u8, id: u16) -> bool {
+    unsafe {
+        let capacity = *base.cast::<u16>();
+        let element_size_val = element_size(base);
+
         let gen_ptr = generation_ptr(base, capacity, element_size_val);
         let old_gen = *gen_ptr.add(id as usize);
-        ptr::write(gen_ptr.add(id as usize), old_gen.wrapping_add(1));
+        let new_gen = next_generation(old_gen);
+        ptr::write(gen_ptr.add(id as usize), new_gen);
 
         let free_top_p = free_top_ptr(base, capacity, element_size_val);
         let free_top = *free_top_p;
@@ -489,7 +1242,7 @@ pub unsafe fn remove(base: *mut u8, id: u16, generation: u16) -> bool {
         ptr::write(free_stk_ptr.add(free_top as usize), id);
         ptr::write(free_top_p, free_top + 1);
 
-        true
+        new_gen < old_gen
     }
 }
 
@@ -519,6 +1272,36 @@ pub unsafe fn is_alive(base: *mut u8, id: u16, generation: u16) -> bool {
     }
 }
 
+/// Check a batch of handles, writing whether `handles[i]` is alive to `out[i]`.
+///
+/// Unlike `is_alive` each handle gets the full check (id in range, matching generation,
+/// occupied), and out of range ids are reported as dead. The header is read only once for
+/// the whole batch.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn are_alive(base: *const u8, handles: &[(u16, u16)], out: &mut [bool]) {
+    unsafe {
+        debug_validate_slotmap(base);
+        debug_assert!(
+            out.len() >= handles.len(),
+            "out ({}) must hold a result for every handle ({})",
+            out.len(),
+            handles.len()
+        );
+
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+        let gen_ptr = generation_ptr(base.cast_mut(), capacity, element_size);
+        let id_to_idx_ptr = id_to_index_ptr(base.cast_mut(), capacity, element_size);
+
+        for (alive, &(id, generation)) in out.iter_mut().zip(handles) {
+            *alive = id < capacity
+                && *gen_ptr.add(id as usize) == generation
+                && *id_to_idx_ptr.add(id as usize) != INVALID_U16;
+        }
+    }
+}
+
 /// Get a pointer to the generation array
 /// # Safety
 /// IMPORTANT: The returned array is indexed by ID, not by dense index.
@@ -615,6 +1398,42 @@ pub const unsafe fn element_size(base: *const u8) -> u32 {
     }
 }
 
+/// Get capacity (maximum number of elements) from header
+/// # Safety
+/// `base` must point to a valid initialized slot map and be 4-byte aligned.
+#[must_use]
+#[inline]
+pub const unsafe fn capacity(base: *const u8) -> u16 {
+    unsafe { *base.cast::<u16>() }
+}
+
+/// Get number of free IDs (`free_top`), i.e. how many more elements can be allocated
+/// # Safety
+/// `base` must point to a valid initialized slot map and be 4-byte aligned.
+#[must_use]
+pub const unsafe fn free_count(base: *const u8) -> u16 {
+    unsafe {
+        let capacity = capacity(base);
+        *free_top_ptr(base.cast_mut(), capacity, element_size(base))
+    }
+}
+
+/// Check if all IDs are in use
+/// # Safety
+/// `base` must point to a valid initialized slot map and be 4-byte aligned.
+#[must_use]
+pub const unsafe fn is_full(base: *const u8) -> bool {
+    unsafe { free_count(base) == 0 }
+}
+
+/// Check if there are no live elements
+/// # Safety
+/// `base` must point to a valid initialized slot map and be 4-byte aligned.
+#[must_use]
+pub const unsafe fn is_empty(base: *const u8) -> bool {
+    unsafe { element_count(base) == 0 }
+}
+
 /// Insert raw bytes at handle (id, generation), validating first.
 /// Returns true if successful, false if the handle is invalid
 /// # Safety
@@ -630,6 +1449,23 @@ pub unsafe fn insert_if_alive(base: *mut u8, id: u16, generation: u16, src: *con
     }
 }
 
+/// Run `f` on the value of a valid handle, for in-place read-modify-write.
+/// Returns true if `f` was called, false (without calling it) if the handle is invalid.
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `f` must not use the value pointer after returning, or allocate or remove elements
+pub unsafe fn update<F: FnOnce(*mut u8)>(base: *mut u8, id: u16, generation: u16, f: F) -> bool {
+    unsafe {
+        let Some(value_ptr) = get_value_ptr(base, id, generation) else {
+            return false;
+        };
+
+        f(value_ptr);
+        refresh_checksum(base);
+        true
+    }
+}
+
 /// Get value pointer for a valid handle
 /// Returns None if the handle is invalid
 /// # Safety
@@ -647,3 +1483,1338 @@ pub unsafe fn get_value_ptr(base: *mut u8, id: u16, generation: u16) -> Option<*
         Some(base.add(offset))
     }
 }
+
+/// Copy the value of a valid handle out into an `N`-byte array.
+/// Returns None if the handle is invalid. `N` must equal the element size.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn read_value<const N: usize>(
+    base: *mut u8,
+    id: u16,
+    generation: u16,
+) -> Option<[u8; N]> {
+    unsafe {
+        debug_assert_eq!(N, element_size(base) as usize, "N must equal element_size");
+
+        let src = get_value_ptr(base, id, generation)?;
+        let mut value = [0u8; N];
+        ptr::copy_nonoverlapping(src, value.as_mut_ptr(), N);
+        Some(value)
+    }
+}
+
+/// Copy an `N`-byte array into the value of a valid handle, like `insert`.
+/// Returns false if the handle is invalid. `N` must equal the element size.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn write_value<const N: usize>(
+    base: *mut u8,
+    id: u16,
+    generation: u16,
+    value: [u8; N],
+) -> bool {
+    unsafe {
+        debug_assert_eq!(N, element_size(base) as usize, "N must equal element_size");
+
+        insert(base, id, generation, value.as_ptr())
+    }
+}
+
+/// Get value pointers for `N` handles at once, for mutating several elements together.
+///
+/// Returns `None` if any handle is invalid or if two handles resolve to the same element,
+/// so the returned pointers never alias each other.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn get_disjoint_mut<const N: usize>(
+    base: *mut u8,
+    handles: [(u16, u16); N],
+) -> Option<[*mut u8; N]> {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let element_size_val = element_size(base) as usize;
+
+        let mut indices = [0u16; N];
+        for (slot, &(id, generation)) in indices.iter_mut().zip(&handles) {
+            *slot = validate_handle(base, id, generation)?;
+        }
+
+        // Reject aliasing: every dense index must be distinct
+        for (i, index) in indices.iter().enumerate() {
+            if indices[..i].contains(index) {
+                return None;
+            }
+        }
+
+        Some(indices.map(|index| base.add(VALUES_OFFSET + index as usize * element_size_val)))
+    }
+}
+
+/// Count the live elements whose value satisfies `pred`.
+/// Scans the dense values region (O(len)) and calls `pred` with a pointer to each value.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn count_where(base: *const u8, mut pred: impl FnMut(*const u8) -> bool) -> u16 {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let len = element_count(base);
+        let elem_size = element_size(base) as usize;
+
+        let mut count = 0;
+        for index in 0..len as usize {
+            if pred(base.add(VALUES_OFFSET + index * elem_size)) {
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+/// Allocate every remaining free slot, writing the resulting handles into `out`.
+/// Returns the number of handles written.
+///
+/// Values of the new slots are left uninitialized, so the caller is expected to fill
+/// each one through its handle.
+///
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `out` must hold at least as many entries as there are free IDs
+pub unsafe fn allocate_all(base: *mut u8, out: &mut [(u16, u16)]) -> u16 {
+    unsafe {
+        let free_top = free_count(base);
+        debug_assert!(
+            out.len() >= free_top as usize,
+            "out ({}) must hold all free IDs ({free_top})",
+            out.len()
+        );
+
+        allocate_batch(base, free_top, out)
+    }
+}
+
+/// Allocate up to `count` slots in one go, writing the resulting handles into `out`.
+///
+/// Returns the number of handles written, which is less than `count` if the free stack
+/// runs dry or `out` is shorter than `count`. `len` and `free_top` are read and
+/// written once for the whole batch. Handles come out in the same order as repeated
+/// `allocate` calls would produce them, and values are left uninitialized.
+///
+/// # Safety
+/// `base` must point to a valid initialized slot map
+pub unsafe fn allocate_batch(base: *mut u8, count: u16, out: &mut [(u16, u16)]) -> u16 {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+        let len_ptr = base.add(2).cast::<u16>();
+        let len = *len_ptr;
+
+        let free_top_p = free_top_ptr(base, capacity, element_size);
+        let free_top = *free_top_p;
+
+        let free_stk_ptr = free_stack_ptr(base, capacity, element_size);
+        let id_to_idx_ptr = id_to_index_ptr(base, capacity, element_size);
+        let idx_to_id_ptr = index_to_id_ptr(base, capacity, element_size);
+        let gen_ptr = generation_ptr(base, capacity, element_size);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let count = out.len().min(count.min(free_top) as usize) as u16;
+
+        // Pop in the same order as repeated allocate() calls would
+        for (i, slot) in out.iter_mut().take(count as usize).enumerate() {
+            let id = *free_stk_ptr.add(free_top as usize - 1 - i);
+            #[allow(clippy::cast_possible_truncation)]
+            let index = len + i as u16;
+            ptr::write(id_to_idx_ptr.add(id as usize), index);
+            ptr::write(idx_to_id_ptr.add(index as usize), id);
+            *slot = (id, *gen_ptr.add(id as usize));
+        }
+
+        ptr::write(len_ptr, len + count);
+        ptr::write(free_top_p, free_top - count);
+
+        refresh_checksum(base);
+
+        count
+    }
+}
+
+/// Remove every handle in `handles` that is alive, skipping stale and duplicate entries.
+/// Returns the number of elements actually removed.
+///
+/// Each handle is validated at the moment it is processed, since an earlier swap-remove
+/// in the same batch may have moved its element to another dense index.
+///
+/// # Safety
+/// `base` must point to a valid initialized slot map
+pub unsafe fn remove_batch(base: *mut u8, handles: &[(u16, u16)]) -> u16 {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let mut removed = 0;
+        for &(id, generation) in handles {
+            if let Some(index) = validate_handle(base, id, generation)
+                && element_count(base) > 0
+            {
+                remove_at_index(base, index);
+                removed += 1;
+            }
+        }
+
+        refresh_checksum(base);
+
+        removed
+    }
+}
+
+/// Get the current dense index of an occupied `id`, ignoring generation.
+/// Returns None if `id` is out of range or not currently in use.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn dense_index_of_id(base: *const u8, id: u16) -> Option<u16> {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        if id >= capacity {
+            return None;
+        }
+
+        let id_to_idx_ptr = id_to_index_ptr(base.cast_mut(), capacity, element_size(base));
+        let index = *id_to_idx_ptr.add(id as usize);
+        if index == INVALID_U16 {
+            return None;
+        }
+
+        Some(index)
+    }
+}
+
+/// Sort `handles` ascending by their current dense index, moving dead handles to the end.
+///
+/// Accessing the values in the resulting order walks the values region sequentially,
+/// which is friendlier to the cache than the original handle order.
+/// The ordering is only valid until the next allocate/remove (or anything else that moves
+/// elements), after which the list must be sorted again.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn sort_handles_by_locality(base: *const u8, handles: &mut [(u16, u16)]) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        handles.sort_unstable_by_key(|&(id, generation)| {
+            validate_handle(base.cast_mut(), id, generation).map_or(u32::MAX, u32::from)
+        });
+    }
+}
+
+/// Initialize the value at handle (id, generation) in place.
+///
+/// If the handle is alive, `init` is called with a pointer to the slot so the payload can be
+/// written directly, without staging it in a separate buffer first.
+/// Returns false (without calling `init`) if the handle is invalid.
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `init` must not write more than `element_size` bytes through the pointer
+pub unsafe fn insert_with(
+    base: *mut u8,
+    id: u16,
+    generation: u16,
+    init: impl FnOnce(*mut u8),
+) -> bool {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let element_size = element_size(base);
+
+        let Some(index) = validate_handle(base, id, generation) else {
+            return false;
+        };
+
+        init(base.add(VALUES_OFFSET + (index as usize) * (element_size as usize)));
+        refresh_checksum(base);
+        true
+    }
+}
+
+/// Allocate a new slot and initialize its value in place.
+///
+/// Fused version of `allocate` followed by `insert_with`: `init` is called with a pointer to
+/// the freshly appended dense slot. Returns None (without calling `init`) if the map is full.
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `init` must not write more than `element_size` bytes through the pointer
+pub unsafe fn allocate_with_closure(
+    base: *mut u8,
+    init: impl FnOnce(*mut u8),
+) -> Option<(u16, u16)> {
+    unsafe {
+        let (id, generation) = allocate(base)?;
+
+        // The new element is always appended at the end of the dense array
+        let index = element_count(base) - 1;
+        let element_size = element_size(base);
+        init(base.add(VALUES_OFFSET + (index as usize) * (element_size as usize)));
+        refresh_checksum(base);
+
+        Some((id, generation))
+    }
+}
+
+/// Allocate a new slot and return its packed handle.
+/// See `allocate`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn allocate_handle(base: *mut u8) -> Option<Handle> {
+    unsafe { allocate(base).map(Handle::from) }
+}
+
+/// Check if a packed handle is alive.
+/// Unlike `is_alive`, an out of range id is reported as not alive.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn is_alive_handle(base: *mut u8, handle: Handle) -> bool {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        validate_handle(base, handle.id(), handle.generation()).is_some()
+    }
+}
+
+/// Remove by packed handle. See `remove`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn remove_handle(base: *mut u8, handle: Handle) -> bool {
+    unsafe { remove(base, handle.id(), handle.generation()) }
+}
+
+/// Get value pointer for a valid packed handle. See `get_value_ptr`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn get_value_ptr_handle(base: *mut u8, handle: Handle) -> Option<*mut u8> {
+    unsafe { get_value_ptr(base, handle.id(), handle.generation()) }
+}
+
+/// Iterator over live elements in dense order, created by `iter`.
+/// Yields `(id, generation, value_ptr)` for each dense index from 0 to len.
+pub struct SlotIter {
+    values: *mut u8,
+    index_to_id: *const u16,
+    generation: *const u16,
+    element_size: usize,
+    index: u16,
+    len: u16,
+}
+
+impl Iterator for SlotIter {
+    type Item = (u16, u16, *mut u8);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len {
+            return None;
+        }
+
+        let index = self.index as usize;
+        self.index += 1;
+
+        unsafe {
+            let id = *self.index_to_id.add(index);
+            let generation = *self.generation.add(id as usize);
+            Some((id, generation, self.values.add(index * self.element_size)))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.len - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for SlotIter {}
+
+/// Iterate over all live elements in dense order.
+///
+/// The dense array has no holes, so this is a linear walk over the values region.
+/// Mutating the map (allocate/remove/clear) while the iterator is in use invalidates it;
+/// writing through the yielded value pointers is fine.
+/// # Safety
+/// `base` must point to a valid initialized slot map that outlives the iterator.
+pub unsafe fn iter(base: *mut u8) -> SlotIter {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+
+        SlotIter {
+            values: base.add(VALUES_OFFSET),
+            index_to_id: index_to_id_ptr(base, capacity, element_size),
+            generation: generation_ptr(base, capacity, element_size),
+            element_size: element_size as usize,
+            index: 0,
+            len: element_count(base),
+        }
+    }
+}
+
+/// Iterator over live elements in ascending id order, created by `iter_by_id`.
+/// Yields `(id, generation, value_ptr)` for each occupied id.
+pub struct StableIter {
+    values: *mut u8,
+    id_to_index: *const u16,
+    generation: *const u16,
+    element_size: usize,
+    id: u16,
+    remaining: u16,
+}
+
+impl Iterator for StableIter {
+    type Item = (u16, u16, *mut u8);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Every live id is below capacity, so running out of live elements ends the walk
+        while self.remaining > 0 {
+            let id = self.id;
+            self.id += 1;
+
+            unsafe {
+                let index = *self.id_to_index.add(id as usize);
+                if index == INVALID_U16 {
+                    continue;
+                }
+                self.remaining -= 1;
+                let generation = *self.generation.add(id as usize);
+                return Some((
+                    id,
+                    generation,
+                    self.values.add(index as usize * self.element_size),
+                ));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl ExactSizeIterator for StableIter {}
+
+/// Iterate over all live elements in ascending id order.
+///
+/// Unlike `iter`, the order does not depend on the dense layout, so it is reproducible
+/// across runs with different removal orders. The cost is a walk over the ids, O(capacity)
+/// in the worst case instead of O(len). The same mutation rules as for `iter` apply.
+/// # Safety
+/// `base` must point to a valid initialized slot map that outlives the iterator.
+pub unsafe fn iter_by_id(base: *mut u8) -> StableIter {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+
+        StableIter {
+            values: base.add(VALUES_OFFSET),
+            id_to_index: id_to_index_ptr(base, capacity, element_size),
+            generation: generation_ptr(base, capacity, element_size),
+            element_size: element_size as usize,
+            id: 0,
+            remaining: element_count(base),
+        }
+    }
+}
+
+/// Get value pointer for a dense index, without going through a handle.
+/// Returns None if `index >= len`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn get_value_ptr_by_index(base: *mut u8, index: u16) -> Option<*mut u8> {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        if index >= element_count(base) {
+            return None;
+        }
+
+        let offset = VALUES_OFFSET + (index as usize) * (element_size(base) as usize);
+        Some(base.add(offset))
+    }
+}
+
+/// Write `element_size` bytes from `src` to the value at dense `index`, without any
+/// validation.
+///
+/// This is the write counterpart of `get_value_ptr_by_index` for trusted hot loops, such as
+/// filling freshly allocated slots while deserializing. No handle, generation or bounds check
+/// is done in release builds.
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `index` MUST be less than the current len. An index at or past len writes into free
+///   slots or past the values region, corrupting the map or other memory.
+/// - `src` must be valid for reads of `element_size` bytes
+#[inline]
+pub unsafe fn insert_unchecked(base: *mut u8, index: u16, src: *const u8) {
+    unsafe {
+        debug_assert!(
+            index < element_count(base),
+            "index ({index}) must be less than len ({})",
+            element_count(base)
+        );
+
+        let element_size = element_size(base) as usize;
+        let offset = VALUES_OFFSET + index as usize * element_size;
+        ptr::copy_nonoverlapping(src, base.add(offset), element_size);
+        refresh_checksum(base);
+    }
+}
+
+/// Move a slot map into a larger buffer, initializing `dst` with `dst_capacity`.
+///
+/// Dense values, mappings, generations and the free stack are carried over, so every handle
+/// that was valid in `src` is valid in `dst` and resolves to the same value. The newly
+/// available IDs (`src capacity..dst_capacity`) are placed below the existing free stack,
+/// so they are handed out only after the IDs that were already free.
+/// The trailer moves because the values region grows, so this is not a flat copy.
+///
+/// Returns false, leaving `dst` untouched, if `dst_capacity` is less than the capacity of `src`.
+/// # Safety
+/// - `src` must point to a valid initialized slot map
+/// - `dst` must point to memory of at least `layout_size(dst_capacity, element_size)` bytes,
+///   aligned like `src` (4 bytes, or as returned by `init_aligned`), not overlapping `src`
+pub unsafe fn grow(dst: *mut u8, dst_capacity: u16, src: *const u8) -> bool {
+    unsafe {
+        debug_validate_slotmap(src);
+
+        let src_mut = src.cast_mut();
+        let src_capacity = *src.cast::<u16>();
+        let element_size = element_size(src);
+        let len = element_count(src);
+        let src_free_top = *free_top_ptr(src_mut, src_capacity, element_size);
+
+        if dst_capacity < src_capacity {
+            return false;
+        }
+
+        init(dst, dst_capacity, element_size);
+        ptr::write(
+            dst.add(trailer_offset(dst_capacity, element_size) + 10)
+                .cast::<u16>(),
+            element_align(src),
+        );
+
+        // Dense values
+        ptr::copy_nonoverlapping(
+            src.add(VALUES_OFFSET),
+            dst.add(VALUES_OFFSET),
+            len as usize * element_size as usize,
+        );
+
+        // Per-ID and per-index arrays, the tail keeps the values written by init()
+        let cap = src_capacity as usize;
+        ptr::copy_nonoverlapping(
+            id_to_index_ptr(src_mut, src_capacity, element_size),
+            id_to_index_ptr(dst, dst_capacity, element_size),
+            cap,
+        );
+        ptr::copy_nonoverlapping(
+            index_to_id_ptr(src_mut, src_capacity, element_size),
+            index_to_id_ptr(dst, dst_capacity, element_size),
+            cap,
+        );
+        ptr::copy_nonoverlapping(
+            generation_ptr(src_mut, src_capacity, element_size),
+            generation_ptr(dst, dst_capacity, element_size),
+            cap,
+        );
+
+        // Free stack: new high IDs at the bottom, then the existing stack on top
+        let new_ids = dst_capacity - src_capacity;
+        let dst_free_stk_ptr = free_stack_ptr(dst, dst_capacity, element_size);
+        for i in 0..new_ids {
+            ptr::write(dst_free_stk_ptr.add(i as usize), src_capacity + i);
+        }
+        ptr::copy_nonoverlapping(
+            free_stack_ptr(src_mut, src_capacity, element_size),
+            dst_free_stk_ptr.add(new_ids as usize),
+            src_free_top as usize,
+        );
+
+        ptr::write(dst.add(2).cast::<u16>(), len);
+        ptr::write(
+            free_top_ptr(dst, dst_capacity, element_size),
+            new_ids + src_free_top,
+        );
+
+        refresh_checksum(dst);
+
+        true
+    }
+}
+
+/// Copy the live elements of `src` into a fresh map of `dst_capacity`, renumbering them.
+///
+/// The element at dense index `i` gets id `i` with generation 1, so the result is as compact
+/// as possible, but unlike `grow` every existing handle is invalidated. For each dense index
+/// `i < remap.len()` the pair `(old_handle, new_handle)` is written to `remap[i]`, pass an
+/// empty slice if the mapping is not needed.
+///
+/// Returns false, leaving `dst` untouched, if `dst_capacity` is less than the len of `src`.
+/// # Safety
+/// - `src` must point to a valid initialized slot map
+/// - `dst` must point to memory of at least `layout_size(dst_capacity, element_size)` bytes,
+///   aligned like `src` (4 bytes, or as returned by `init_aligned`), not overlapping `src`
+/// - `dst_capacity` must not be 0
+pub unsafe fn shrink_into(
+    dst: *mut u8,
+    dst_capacity: u16,
+    src: *const u8,
+    remap: &mut [(Handle, Handle)],
+) -> bool {
+    unsafe {
+        debug_validate_slotmap(src);
+
+        let src_mut = src.cast_mut();
+        let src_capacity = *src.cast::<u16>();
+        let element_size = element_size(src);
+        let len = element_count(src);
+
+        if dst_capacity < len {
+            return false;
+        }
+
+        init(dst, dst_capacity, element_size);
+        ptr::write(
+            dst.add(trailer_offset(dst_capacity, element_size) + 10)
+                .cast::<u16>(),
+            element_align(src),
+        );
+
+        // Dense order is kept, only the ids change
+        ptr::copy_nonoverlapping(
+            src.add(VALUES_OFFSET),
+            dst.add(VALUES_OFFSET),
+            len as usize * element_size as usize,
+        );
+
+        let src_idx_to_id_ptr = index_to_id_ptr(src_mut, src_capacity, element_size);
+        let src_gen_ptr = generation_ptr(src_mut, src_capacity, element_size);
+        let id_to_idx_ptr = id_to_index_ptr(dst, dst_capacity, element_size);
+        let idx_to_id_ptr = index_to_id_ptr(dst, dst_capacity, element_size);
+        for index in 0..len {
+            ptr::write(id_to_idx_ptr.add(index as usize), index);
+            ptr::write(idx_to_id_ptr.add(index as usize), index);
+
+            if let Some(entry) = remap.get_mut(index as usize) {
+                let old_id = *src_idx_to_id_ptr.add(index as usize);
+                let old_generation = *src_gen_ptr.add(old_id as usize);
+                *entry = (Handle::new(old_id, old_generation), Handle::new(index, 1));
+            }
+        }
+
+        // Free stack holds the unused ids len..dst_capacity, highest popped first like init()
+        let free_count = dst_capacity - len;
+        let free_stk_ptr = free_stack_ptr(dst, dst_capacity, element_size);
+        for i in 0..free_count {
+            ptr::write(free_stk_ptr.add(i as usize), len + i);
+        }
+
+        ptr::write(dst.add(2).cast::<u16>(), len);
+        ptr::write(free_top_ptr(dst, dst_capacity, element_size), free_count);
+
+        refresh_checksum(dst);
+
+        true
+    }
+}
+
+/// Copy every live element of `src` into newly allocated slots of `dst`.
+///
+/// Elements are taken in the dense order of `src` and get fresh handles in `dst`, since ids
+/// from two maps would otherwise collide. For the `i`th merged element the pair
+/// `(src_handle, dst_handle)` is written to `remap[i]` if `remap` is long enough. Stops early
+/// when `dst` is full, and returns the number of elements merged.
+/// # Safety
+/// - `src` and `dst` must point to valid initialized slot maps with the same element size
+/// - The two maps must not overlap
+pub unsafe fn merge_into(dst: *mut u8, src: *const u8, remap: &mut [(Handle, Handle)]) -> u16 {
+    unsafe {
+        debug_validate_slotmap(src);
+
+        debug_assert_eq!(
+            element_size(src),
+            element_size(dst),
+            "src and dst must have the same element_size"
+        );
+
+        let mut merged = 0;
+        for (src_id, src_generation, src_value) in iter(src.cast_mut()) {
+            let Some((id, generation)) = allocate_and_insert(dst, src_value) else {
+                break;
+            };
+
+            if let Some(entry) = remap.get_mut(merged as usize) {
+                *entry = (
+                    Handle::new(src_id, src_generation),
+                    Handle::new(id, generation),
+                );
+            }
+            merged += 1;
+        }
+
+        merged
+    }
+}
+
+/// Call `f(offset, width)` for every multi-byte field of the layout, in address order:
+/// the header, the trailer header and every entry of the four trailer arrays.
+/// Dense values are opaque and not included.
+fn for_each_field(capacity: u16, element_size: u32, mut f: impl FnMut(usize, usize)) {
+    // Header: capacity, len, element_size
+    f(0, 2);
+    f(2, 2);
+    f(4, 4);
+
+    // Trailer header: magic, elem_size, free_top, element_align, checksum
+    let trailer_off = trailer_offset(capacity, element_size);
+    f(trailer_off, 4);
+    f(trailer_off + 4, 4);
+    f(trailer_off + 8, 2);
+    f(trailer_off + 10, 2);
+    f(trailer_off + CHECKSUM_OFFSET, 4);
+
+    let arrays_off = align4(trailer_off + TRAILER_HEADER_SIZE);
+    for i in 0..4 * capacity as usize {
+        f(arrays_off + i * size_of::<u16>(), 2);
+    }
+}
+
+/// Serialize the slot map into `out` in a canonical little-endian form.
+///
+/// Every header, trailer and array field is written little-endian, so the result can be
+/// loaded with `from_portable` on a machine of either endianness. The dense values region is
+/// copied verbatim: if the payload itself is endian-sensitive, converting it is the caller's
+/// responsibility.
+/// # Panics
+/// If `out` is smaller than `layout_size(capacity, element_size)`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn to_portable(base: *const u8, out: &mut [u8]) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+        let size = layout_size(capacity, element_size);
+
+        out[..size].copy_from_slice(core::slice::from_raw_parts(base, size));
+
+        for_each_field(capacity, element_size, |offset, width| {
+            if width == 2 {
+                let value = *base.add(offset).cast::<u16>();
+                out[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+            } else {
+                let value = *base.add(offset).cast::<u32>();
+                out[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            }
+        });
+    }
+}
+
+/// Load a slot map written by `to_portable` into `base`, converting every field to native
+/// byte order. Dense values are copied verbatim.
+/// # Panics
+/// If `input` is shorter than the layout described by its own header.
+/// # Safety
+/// `base` must be 4-byte aligned and writable for `layout_size(capacity, element_size)`
+/// bytes, where `capacity` and `element_size` are read from the header in `input`. Check
+/// them first if `input` is untrusted.
+pub unsafe fn from_portable(base: *mut u8, input: &[u8]) {
+    debug_assert_eq!((base as usize) & 3, 0, "base must be 4-byte aligned");
+
+    let capacity = u16::from_le_bytes([input[0], input[1]]);
+    let element_size = u32::from_le_bytes([input[4], input[5], input[6], input[7]]);
+    let size = layout_size(capacity, element_size);
+    let input = &input[..size];
+
+    unsafe {
+        ptr::copy_nonoverlapping(input.as_ptr(), base, size);
+
+        for_each_field(capacity, element_size, |offset, width| {
+            if width == 2 {
+                let value = u16::from_le_bytes([input[offset], input[offset + 1]]);
+                ptr::write(base.add(offset).cast::<u16>(), value);
+            } else {
+                let mut bytes = [0u8; 4];
+                bytes.copy_from_slice(&input[offset..offset + 4]);
+                ptr::write(base.add(offset).cast::<u32>(), u32::from_le_bytes(bytes));
+            }
+        });
+
+        debug_validate_slotmap(base);
+    }
+}
+
+/// Allocate a new slot and copy `element_size` bytes from `src` into it.
+/// Returns the new handle, or None if the map is full, in which case `src` is not read.
+///
+/// Cheaper than `allocate` followed by `insert`, since the new handle needs no validation.
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `src` must be valid for reads of `element_size` bytes if the map is not full
+pub unsafe fn allocate_and_insert(base: *mut u8, src: *const u8) -> Option<(u16, u16)> {
+    unsafe {
+        let element_size = element_size(base) as usize;
+        allocate_with_closure(base, |slot| {
+            ptr::copy_nonoverlapping(src, slot, element_size);
+        })
+    }
+}
+
+/// Remove by handle like `remove`, then zero the vacated dense slot.
+///
+/// Swap-remove moves the last element into the removed slot, so the only slot that no longer
+/// holds a live value is the old tail (dense index `len` after the removal). Zeroing it means
+/// the removed bytes do not stay resident in the buffer.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn remove_and_zero(base: *mut u8, id: u16, generation: u16) -> bool {
+    unsafe {
+        if !remove(base, id, generation) {
+            return false;
+        }
+
+        let elem_size = element_size(base) as usize;
+        let vacated = VALUES_OFFSET + element_count(base) as usize * elem_size;
+        ptr::write_bytes(base.add(vacated), 0, elem_size);
+        refresh_checksum(base);
+        true
+    }
+}
+
+/// Clear the slot map like `clear`, and also zero the whole dense values region.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn clear_zeroed(base: *mut u8) {
+    unsafe {
+        clear(base);
+
+        let values_size = capacity(base) as usize * element_size(base) as usize;
+        ptr::write_bytes(base.add(VALUES_OFFSET), 0, values_size);
+        refresh_checksum(base);
+    }
+}
+
+/// Swap the dense positions of two live elements, keeping their handles unchanged.
+/// Returns false if either handle is invalid or both refer to the same id.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn swap(base: *mut u8, a_id: u16, a_gen: u16, b_id: u16, b_gen: u16) -> bool {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        if a_id == b_id {
+            return false;
+        }
+        let Some(a_index) = validate_handle(base, a_id, a_gen) else {
+            return false;
+        };
+        let Some(b_index) = validate_handle(base, b_id, b_gen) else {
+            return false;
+        };
+
+        let capacity = *base.cast::<u16>();
+        let element_size_val = element_size(base);
+        let elem_size = element_size_val as usize;
+
+        ptr::swap_nonoverlapping(
+            base.add(VALUES_OFFSET + a_index as usize * elem_size),
+            base.add(VALUES_OFFSET + b_index as usize * elem_size),
+            elem_size,
+        );
+
+        let id_to_idx_ptr = id_to_index_ptr(base, capacity, element_size_val);
+        ptr::write(id_to_idx_ptr.add(a_id as usize), b_index);
+        ptr::write(id_to_idx_ptr.add(b_id as usize), a_index);
+
+        let idx_to_id_ptr = index_to_id_ptr(base, capacity, element_size_val);
+        ptr::write(idx_to_id_ptr.add(a_index as usize), b_id);
+        ptr::write(idx_to_id_ptr.add(b_index as usize), a_id);
+
+        refresh_checksum(base);
+
+        true
+    }
+}
+
+/// Get the handle (id, generation) of the element at a dense index.
+/// Returns None if `index >= len`.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn handle_for_index(base: *const u8, index: u16) -> Option<(u16, u16)> {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        if index >= element_count(base) {
+            return None;
+        }
+
+        let capacity = *base.cast::<u16>();
+        let element_size = element_size(base);
+
+        let id = *index_to_id_ptr(base.cast_mut(), capacity, element_size).add(index as usize);
+        let generation = *generation_ptr(base.cast_mut(), capacity, element_size).add(id as usize);
+
+        Some((id, generation))
+    }
+}
+
+/// Find the handle of the element whose value starts at `ptr`.
+///
+/// Returns None if `ptr` is outside the live values region or does not point at the start
+/// of an element, and always for zero-size elements, which all share one address.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn handle_from_value_ptr(base: *mut u8, ptr: *const u8) -> Option<(u16, u16)> {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let elem_size = element_size(base) as usize;
+        if elem_size == 0 {
+            return None;
+        }
+
+        let offset = ptr.addr().checked_sub(base.add(VALUES_OFFSET).addr())?;
+        if !offset.is_multiple_of(elem_size) {
+            return None;
+        }
+
+        // handle_for_index rejects indices at or past len
+        let index = u16::try_from(offset / elem_size).ok()?;
+        handle_for_index(base, index)
+    }
+}
+
+/// Check if `id` is currently occupied, regardless of generation.
+/// Returns false for out of range ids.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn contains_id(base: *const u8, id: u16) -> bool {
+    unsafe { dense_index_of_id(base, id).is_some() }
+}
+
+/// Write a bitmap of occupied ids to `out`: bit `i % 8` of `out[i / 8]` is set iff id `i`
+/// is currently in use.
+///
+/// The first `capacity.div_ceil(8)` bytes are overwritten, including the unused high bits
+/// of the last byte (cleared), and the rest of `out` is left alone.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+/// # Panics
+/// If `out` is shorter than `capacity.div_ceil(8)` bytes.
+pub unsafe fn write_occupancy_bitmap(base: *const u8, out: &mut [u8]) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let id_to_idx_ptr = id_to_index_ptr(base.cast_mut(), capacity, element_size(base));
+
+        let out = &mut out[..capacity.div_ceil(8) as usize];
+        out.fill(0);
+        for id in 0..capacity as usize {
+            if *id_to_idx_ptr.add(id) != INVALID_U16 {
+                out[id / 8] |= 1 << (id % 8);
+            }
+        }
+    }
+}
+
+/// Get the live generation of an occupied `id`, or None if the id is free or out of range.
+/// Together with `contains_id` this lets tooling rebuild a valid handle from a raw id.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn current_generation(base: *const u8, id: u16) -> Option<u16> {
+    unsafe {
+        dense_index_of_id(base, id)?;
+
+        let gen_ptr = generation_ptr(base.cast_mut(), capacity(base), element_size(base));
+        Some(*gen_ptr.add(id as usize))
+    }
+}
+
+/// Get the highest generation of any id, free or occupied.
+/// A value far above the rest points at an id that is recycled much more than others.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+#[must_use]
+pub unsafe fn max_generation(base: *const u8) -> u16 {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let gen_ptr = generation_ptr(base.cast_mut(), capacity, element_size(base));
+        core::slice::from_raw_parts(gen_ptr, capacity as usize)
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Count the ids per generation range, free or occupied, into `out`.
+///
+/// The generation range `0..=u16::MAX` is split into `out.len()` equal buckets, so id
+/// `i` is counted in `out[generation[i] * out.len() / 65536]`. `out` is zeroed first.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn generation_histogram(base: *const u8, out: &mut [u32]) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        out.fill(0);
+        if out.is_empty() {
+            return;
+        }
+
+        let capacity = *base.cast::<u16>();
+        let gen_ptr = generation_ptr(base.cast_mut(), capacity, element_size(base));
+        for &generation in core::slice::from_raw_parts(gen_ptr, capacity as usize) {
+            out[generation as usize * out.len() / 0x1_0000] += 1;
+        }
+    }
+}
+
+/// Get the live dense values region as `(pointer, byte length)`.
+///
+/// The values have no holes, so this is `len * element_size` bytes starting at the values
+/// offset, suitable for tight (e.g. SIMD) loops over all elements.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn values_slice(base: *mut u8) -> (*mut u8, usize) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let byte_len = element_count(base) as usize * element_size(base) as usize;
+        (base.add(VALUES_OFFSET), byte_len)
+    }
+}
+
+/// Check if two slot maps hold the same live values, ignoring dense order and ids.
+///
+/// The element sizes and lens must match, and every value must occur as many times in `a`
+/// as in `b`, comparing values as raw bytes. Works without allocating by counting the
+/// occurrences of each value of `a` in both maps, so it is O(len²) and meant for tests and
+/// tooling.
+/// # Safety
+/// `a` and `b` must point to valid initialized slot maps.
+#[must_use]
+pub unsafe fn equal_contents(a: *const u8, b: *const u8) -> bool {
+    unsafe {
+        debug_validate_slotmap(a);
+        debug_validate_slotmap(b);
+
+        let elem_size = element_size(a) as usize;
+        let len = element_count(a) as usize;
+        if element_size(b) as usize != elem_size || element_count(b) as usize != len {
+            return false;
+        }
+
+        let values_a = core::slice::from_raw_parts(a.add(VALUES_OFFSET), len * elem_size);
+        let values_b = core::slice::from_raw_parts(b.add(VALUES_OFFSET), len * elem_size);
+        if elem_size == 0 {
+            return true;
+        }
+
+        let count = |values: &[u8], needle: &[u8]| {
+            values
+                .chunks_exact(elem_size)
+                .filter(|value| *value == needle)
+                .count()
+        };
+        values_a
+            .chunks_exact(elem_size)
+            .all(|value| count(values_a, value) == count(values_b, value))
+    }
+}
+
+/// Copy the whole slot map from `src` into `dst`.
+///
+/// Both maps must have the same capacity and element size, so their layouts are byte
+/// identical and this is a single copy of `layout_size` bytes. Afterwards every handle that is
+/// valid in `src` is valid in `dst` and resolves to the same value.
+/// # Safety
+/// - `src` and `dst` must point to valid initialized slot maps with the same capacity and
+///   element size
+/// - The two buffers must not overlap
+pub unsafe fn copy_from(dst: *mut u8, src: *const u8) {
+    unsafe {
+        debug_validate_slotmap(src);
+        debug_validate_slotmap(dst);
+
+        let capacity = capacity(src);
+        let element_size = element_size(src);
+        debug_assert_eq!(
+            capacity,
+            crate::capacity(dst),
+            "capacity mismatch between src and dst"
+        );
+        debug_assert_eq!(
+            element_size,
+            crate::element_size(dst),
+            "element_size mismatch between src and dst"
+        );
+
+        ptr::copy_nonoverlapping(src, dst, layout_size(capacity, element_size));
+    }
+}
+
+/// Copy the whole slot map into a newly allocated buffer.
+///
+/// The buffer is returned as `u32` words so that it is 4-byte aligned and can be used as a
+/// slot map directly (`vec.as_mut_ptr().cast::<u8>()`).
+/// # Safety
+/// `src` must point to a valid initialized slot map.
+#[cfg(feature = "alloc")]
+#[must_use]
+pub unsafe fn clone_into_vec(src: *const u8) -> alloc::vec::Vec<u32> {
+    unsafe {
+        debug_validate_slotmap(src);
+
+        let size = layout_size(capacity(src), element_size(src));
+        let mut words = alloc::vec![0u32; size.div_ceil(4)];
+        ptr::copy_nonoverlapping(src, words.as_mut_ptr().cast::<u8>(), size);
+        words
+    }
+}
+
+/// Keep only the elements for which `f(id, generation, value_ptr)` returns true.
+///
+/// Works like `Vec::retain` in a single pass over the dense array: rejected elements are
+/// swap-removed (generation bump, id pushed on the free stack). Swap-remove moves a not yet
+/// visited element into the current index, so that index is examined again before moving on.
+/// Elements are therefore not necessarily visited in their original dense order.
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `f` must not allocate or remove elements in the same map
+pub unsafe fn retain<F: FnMut(u16, u16, *mut u8) -> bool>(base: *mut u8, mut f: F) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let element_size_val = element_size(base);
+        let elem_size = element_size_val as usize;
+        let idx_to_id_ptr = index_to_id_ptr(base, capacity, element_size_val);
+        let gen_ptr = generation_ptr(base, capacity, element_size_val);
+
+        let mut index = 0;
+        while index < element_count(base) {
+            let id = *idx_to_id_ptr.add(index as usize);
+            let generation = *gen_ptr.add(id as usize);
+            let value_ptr = base.add(VALUES_OFFSET + index as usize * elem_size);
+
+            if f(id, generation, value_ptr) {
+                index += 1;
+            } else {
+                // The former last element now sits at `index`, visit it next
+                remove_at_index(base, index);
+            }
+        }
+
+        refresh_checksum(base);
+    }
+}
+
+/// Remove the elements at dense indices `new_len..len`, keeping the first `new_len`.
+///
+/// The tail elements are retired from the last one down (generation bump, id pushed on
+/// the free stack), with no swapping, so this is cheaper than removing them one by one.
+/// Returns false, changing nothing, if `new_len` is greater than len.
+/// # Safety
+/// `base` must point to a valid initialized slot map.
+pub unsafe fn truncate(base: *mut u8, new_len: u16) -> bool {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        if new_len > element_count(base) {
+            return false;
+        }
+
+        retire_tail(base, new_len, |_, _, _| {});
+
+        refresh_checksum(base);
+
+        true
+    }
+}
+
+/// Remove every element, calling `f(id, generation, value_ptr)` for each one just before it
+/// is retired, like `Vec::drain`.
+///
+/// Elements are taken from the end of the dense array, so no swapping is needed and each
+/// element is seen exactly once. Afterwards the map is empty, with every id on the free stack.
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `f` must not allocate or remove elements in the same map
+pub unsafe fn drain<F: FnMut(u16, u16, *const u8)>(base: *mut u8, f: F) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        retire_tail(base, 0, f);
+
+        refresh_checksum(base);
+    }
+}
+
+/// Retire the elements at dense indices `new_len..len`, last one first, calling `f` with
+/// each element's handle and value before it goes. The len is lowered one element at a time.
+/// The caller must guarantee `new_len <= len`, and refresh the checksum afterwards.
+unsafe fn retire_tail(base: *mut u8, new_len: u16, mut f: impl FnMut(u16, u16, *const u8)) {
+    unsafe {
+        let capacity = *base.cast::<u16>();
+        let element_size_val = element_size(base);
+        let elem_size = element_size_val as usize;
+        let len = element_count(base);
+
+        let idx_to_id_ptr = index_to_id_ptr(base, capacity, element_size_val);
+        let id_to_idx_ptr = id_to_index_ptr(base, capacity, element_size_val);
+        let gen_ptr = generation_ptr(base, capacity, element_size_val);
+        for index in (new_len..len).rev() {
+            let id = *idx_to_id_ptr.add(index as usize);
+            f(
+                id,
+                *gen_ptr.add(id as usize),
+                base.add(VALUES_OFFSET + index as usize * elem_size),
+            );
+
+            ptr::write(idx_to_id_ptr.add(index as usize), INVALID_U16);
+            ptr::write(id_to_idx_ptr.add(id as usize), INVALID_U16);
+            ptr::write(base.add(2).cast::<u16>(), index);
+            retire_id(base, id);
+        }
+    }
+}
+
+/// Call `f(id, generation, value_ptr)` for every live element, in dense order.
+///
+/// Values may be written through `value_ptr`, but the structure must stay as it is.
+/// # Safety
+/// - `base` must point to a valid initialized slot map
+/// - `f` must not allocate or remove elements in the same map
+pub unsafe fn for_each<F: FnMut(u16, u16, *mut u8)>(base: *mut u8, mut f: F) {
+    unsafe {
+        debug_validate_slotmap(base);
+
+        let capacity = *base.cast::<u16>();
+        let element_size_val = element_size(base);
+        let elem_size = element_size_val as usize;
+        let len = element_count(base);
+        let idx_to_id_ptr = index_to_id_ptr(base, capacity, element_size_val);
+        let gen_ptr = generation_ptr(base, capacity, element_size_val);
+
+        for index in 0..len as usize {
+            let id = *idx_to_id_ptr.add(index);
+            let generation = *gen_ptr.add(id as usize);
+            f(id, generation, base.add(VALUES_OFFSET + index * elem_size));
+        }
+
+        refresh_checksum(base);
+    }
+}
+
+/// Write `values` as `[a, b, -, ...]`, showing the `0xFFFF` sentinel as `-`
+fn dump_array(out: &mut impl fmt::Write, name: &str, values: &[u16]) -> fmt::Result {
+    write!(out, "{name:<13}[")?;
+    for (i, &value) in values.iter().enumerate() {
+        if i > 0 {
+            out.write_str(", ")?;
+        }
+        if value == INVALID_U16 {
+            out.write_char('-')?;
+        } else {
+            write!(out, "{value}")?;
+        }
+    }
+    out.write_str("]\n")
+}
+
+/// Write a human-readable report of the slot map state: header fields, all four trailer
+/// arrays (with `0xFFFF` shown as `-`) and the result of the `len + free_top == capacity`
+/// invariant check.
+///
+/// No validation is done first, so this can be used on a map that fails
+/// `debug_validate_slotmap`, as long as the header capacity and element size are intact.
+/// # Errors
+/// Returns an error if writing to `out` fails.
+/// # Safety
+/// `base` must point to an initialized slot map.
+pub unsafe fn debug_dump(base: *const u8, out: &mut impl fmt::Write) -> fmt::Result {
+    unsafe {
+        let capacity = capacity(base);
+        let len = element_count(base);
+        let element_size = element_size(base);
+        let free_top = free_count(base);
+        let cap = capacity as usize;
+        let base_mut = base.cast_mut();
+
+        writeln!(
+            out,
+            "slotmap capacity={capacity} len={len} free_top={free_top} element_size={element_size}"
+        )?;
+
+        let array = |ptr: *mut u16, count: usize| core::slice::from_raw_parts(ptr, count);
+        dump_array(
+            out,
+            "id_to_index:",
+            array(id_to_index_ptr(base_mut, capacity, element_size), cap),
+        )?;
+        dump_array(
+            out,
+            "index_to_id:",
+            array(index_to_id_ptr(base_mut, capacity, element_size), cap),
+        )?;
+        dump_array(
+            out,
+            "generation:",
+            array(generation_ptr(base_mut, capacity, element_size), cap),
+        )?;
+        dump_array(
+            out,
+            "free_stack:",
+            array(
+                free_stack_ptr(base_mut, capacity, element_size),
+                cap.min(free_top as usize),
+            ),
+        )?;
+
+        let invariant_ok = u32::from(len) + u32::from(free_top) == u32::from(capacity);
+        writeln!(
+            out,
+            "invariant len + free_top == capacity: {}",
+            if invariant_ok { "ok" } else { "VIOLATED" }
+        )
+    }
+}
+
+/// `debug_dump` into a newly allocated `String`.
+/// # Safety
+/// `base` must point to an initialized slot map.
+#[cfg(feature = "alloc")]
+#[must_use]
+pub unsafe fn dump_string(base: *const u8) -> alloc::string::String {
+    let mut out = alloc::string::String::new();
+    unsafe {
+        // Writing to a String never fails
+        let _ = debug_dump(base, &mut out);
+    }
+    out
+}
@@ -0,0 +1,202 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+//! Safe typed layer over the raw pointer API.
+//!
+//! [`SlotMap`] borrows a byte buffer for its lifetime and stores values of a single type `T`
+//! in it, using the same memory layout as the raw functions. The element size of the slot map
+//! is always `size_of::<T>()`.
+//!
+//! Values still in the map when the [`SlotMap`] goes out of scope are not dropped; they remain
+//! in the buffer as plain bytes, the same as if they had been passed to `core::mem::forget`.
+
+use crate::{
+    Handle, SlotMapError, alignment, allocate_with_closure, element_count, get_value_ptr, init,
+    is_alive_handle, layout_size, remove, try_validate, values_slice,
+};
+use core::marker::PhantomData;
+use core::{ptr, slice};
+
+/// Typed slot map stored in a borrowed byte buffer.
+pub struct SlotMap<'a, T> {
+    base: *mut u8,
+    _marker: PhantomData<(&'a mut [u8], T)>,
+}
+
+impl<'a, T> SlotMap<'a, T> {
+    /// Initialize a fresh slot map with room for `capacity` values in `buffer`.
+    ///
+    /// # Errors
+    /// Fails if `T` or `buffer` is not suitably aligned, if `capacity` is 0, or if `buffer`
+    /// is smaller than `layout_size(capacity, size_of::<T>())`.
+    pub fn new(buffer: &'a mut [u8], capacity: u16) -> Result<Self, SlotMapError> {
+        let element_size = Self::element_size()?;
+        if capacity == 0 {
+            return Err(SlotMapError::ZeroCapacity);
+        }
+        check_buffer(buffer, layout_size(capacity, element_size))?;
+
+        let base = buffer.as_mut_ptr();
+        unsafe {
+            init(base, capacity, element_size);
+        }
+
+        Ok(Self {
+            base,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Initialize a fresh slot map in a word buffer, which is always 4-byte aligned.
+    ///
+    /// Same as [`SlotMap::new`], for callers that do not already have an aligned byte buffer.
+    ///
+    /// # Errors
+    /// Fails if `T` is not suitably aligned, if `capacity` is 0, or if `buffer` is smaller
+    /// than `layout_size(capacity, size_of::<T>())` bytes.
+    pub fn from_words(buffer: &'a mut [u32], capacity: u16) -> Result<Self, SlotMapError> {
+        let len = size_of_val(buffer);
+        let bytes = unsafe { slice::from_raw_parts_mut(buffer.as_mut_ptr().cast::<u8>(), len) };
+        Self::new(bytes, capacity)
+    }
+
+    /// Attach to a buffer that already contains an initialized slot map of `T` values.
+    ///
+    /// The header, trailer and bookkeeping invariants are validated before attaching.
+    ///
+    /// # Errors
+    /// Fails if the buffer is misaligned, too small, was initialized with a different
+    /// element size, or does not contain a consistent slot map.
+    ///
+    /// # Safety
+    /// Every live value in the buffer must be a valid `T`. The validation only covers the
+    /// slot map bookkeeping, not the value bytes.
+    pub unsafe fn from_initialized(buffer: &'a mut [u8]) -> Result<Self, SlotMapError> {
+        let element_size = Self::element_size()?;
+
+        let base = buffer.as_mut_ptr();
+        try_validate(base, buffer.len())?;
+
+        let found_size = unsafe { crate::element_size(base) };
+        if found_size != element_size {
+            return Err(SlotMapError::ElementSizeMismatch {
+                expected: element_size,
+                actual: found_size,
+            });
+        }
+
+        Ok(Self {
+            base,
+            _marker: PhantomData,
+        })
+    }
+
+    const fn element_size() -> Result<u32, SlotMapError> {
+        const { assert!(size_of::<T>() <= u32::MAX as usize) };
+        if align_of::<T>() > alignment() {
+            return Err(SlotMapError::Misaligned {
+                required: align_of::<T>(),
+            });
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(size_of::<T>() as u32)
+    }
+
+    /// Move `value` into a newly allocated slot and return its handle.
+    ///
+    /// # Errors
+    /// Returns [`SlotMapError::Full`] if all slots are in use; `value` is dropped.
+    pub fn insert(&mut self, value: T) -> Result<Handle, SlotMapError> {
+        unsafe {
+            let (id, generation) =
+                allocate_with_closure(self.base, |slot| ptr::write(slot.cast::<T>(), value))
+                    .ok_or(SlotMapError::Full)?;
+            Ok(Handle::new(id, generation))
+        }
+    }
+
+    /// Get a reference to the value for `handle`, or None if the handle is not alive.
+    #[must_use]
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        unsafe {
+            get_value_ptr(self.base, handle.id(), handle.generation()).map(|ptr| &*ptr.cast::<T>())
+        }
+    }
+
+    /// Get a mutable reference to the value for `handle`, or None if the handle is not alive.
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        unsafe {
+            get_value_ptr(self.base, handle.id(), handle.generation())
+                .map(|ptr| &mut *ptr.cast::<T>())
+        }
+    }
+
+    /// Remove the value for `handle` and return it, or None if the handle is not alive.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        unsafe {
+            let ptr = get_value_ptr(self.base, handle.id(), handle.generation())?;
+            let value = ptr::read(ptr.cast::<T>());
+            remove(self.base, handle.id(), handle.generation());
+            Some(value)
+        }
+    }
+
+    /// All live values as a contiguous slice, in dense order.
+    #[must_use]
+    pub fn values(&self) -> &[T] {
+        unsafe {
+            let (ptr, _) = values_slice(self.base);
+            slice::from_raw_parts(ptr.cast::<T>(), self.len() as usize)
+        }
+    }
+
+    /// All live values as a contiguous mutable slice, in dense order.
+    pub fn values_mut(&mut self) -> &mut [T] {
+        unsafe {
+            let (ptr, _) = values_slice(self.base);
+            slice::from_raw_parts_mut(ptr.cast::<T>(), self.len() as usize)
+        }
+    }
+
+    /// Check if `handle` refers to a live value.
+    #[must_use]
+    pub fn contains(&self, handle: Handle) -> bool {
+        unsafe { is_alive_handle(self.base, handle) }
+    }
+
+    /// Number of live values.
+    #[must_use]
+    pub const fn len(&self) -> u16 {
+        unsafe { element_count(self.base) }
+    }
+
+    /// Check if there are no live values.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Maximum number of values the map can hold.
+    #[must_use]
+    pub const fn capacity(&self) -> u16 {
+        unsafe { crate::capacity(self.base) }
+    }
+}
+
+/// Check alignment and length of `buffer` before touching its contents.
+fn check_buffer(buffer: &[u8], required: usize) -> Result<(), SlotMapError> {
+    if !(buffer.as_ptr() as usize).is_multiple_of(alignment()) {
+        return Err(SlotMapError::Misaligned {
+            required: alignment(),
+        });
+    }
+    if buffer.len() < required {
+        return Err(SlotMapError::BufferTooSmall {
+            required,
+            actual: buffer.len(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,341 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+//! Wide variant of the slot map, with `u32` ids, indices and generations for capacities
+//! beyond 65534.
+//!
+//! The algorithm is identical to the `u16` layout (dense swap-remove, LIFO free stack,
+//! generation bump on remove); only the field widths and offsets differ. The bookkeeping
+//! arrays take twice the memory of the `u16` layout.
+//!
+//! # Memory Layout
+//!
+//! ```text
+//! [ Header (16B) | Dense Values | Trailer ]
+//!
+//! Header:
+//!   +0: capacity (u32), len (u32), element_size (u32), pad (u32)
+//!
+//! Dense Values:
+//!   offset 16, size = capacity * element_size
+//!
+//! Trailer (4-byte aligned):
+//!   - Header (16B): magic (u32), elem_size (u32), free_top (u32), pad (u32)
+//!   - Arrays (each u32[capacity]): id_to_index, index_to_id, generation, free_stack
+//! ```
+//!
+//! Sentinel value for invalid entries: `0xFFFF_FFFF`.
+
+use core::ptr;
+
+const WIDE_TRAILER_MAGIC: u32 = 0x5357_4944; // 'SWID'
+const INVALID_U32: u32 = 0xFFFF_FFFF;
+const HEADER_SIZE: usize = 16; // capacity(4) + len(4) + element_size(4) + pad(4)
+const VALUES_OFFSET: usize = HEADER_SIZE;
+const TRAILER_HEADER_SIZE: usize = 16;
+
+/// Largest supported capacity; `0xFFFF_FFFF` is reserved as the sentinel.
+pub const MAX_CAPACITY32: u32 = INVALID_U32 - 1;
+
+/// Validate header and trailer in debug builds.
+#[inline]
+#[allow(unused_variables)]
+fn debug_validate32(base: *const u8) {
+    #[cfg(debug_assertions)]
+    unsafe {
+        debug_assert_eq!((base as usize) & 3, 0, "base must be 4-byte aligned");
+
+        let capacity = *base.cast::<u32>();
+        let len = *base.add(4).cast::<u32>();
+        let elem_size = *base.add(8).cast::<u32>();
+        debug_assert_ne!(
+            capacity, 0,
+            "capacity must not be 0 (did you call init32()?)"
+        );
+        debug_assert!(
+            len <= capacity,
+            "len ({len}) must not exceed capacity ({capacity})"
+        );
+
+        let trailer_off = trailer_offset(capacity, elem_size);
+        debug_assert_eq!(
+            *base.add(trailer_off).cast::<u32>(),
+            WIDE_TRAILER_MAGIC,
+            "Invalid wide trailer magic at offset {trailer_off}"
+        );
+        let free_top = *base.add(trailer_off + 8).cast::<u32>();
+        debug_assert_eq!(
+            u64::from(len) + u64::from(free_top),
+            u64::from(capacity),
+            "Invariant violated: len ({len}) + free_top ({free_top}) != capacity ({capacity})"
+        );
+    }
+}
+
+#[inline]
+const fn trailer_offset(capacity: u32, element_size: u32) -> usize {
+    let values_size = capacity as usize * element_size as usize;
+    crate::align4(VALUES_OFFSET + values_size)
+}
+
+#[inline]
+const fn arrays_offset(capacity: u32, element_size: u32) -> usize {
+    crate::align4(trailer_offset(capacity, element_size) + TRAILER_HEADER_SIZE)
+}
+
+/// Pointer to trailer array number `which` (0 = `id_to_index`, 1 = `index_to_id`,
+/// 2 = generation, 3 = `free_stack`)
+#[inline]
+const unsafe fn array_ptr(
+    base: *mut u8,
+    capacity: u32,
+    element_size: u32,
+    which: usize,
+) -> *mut u32 {
+    unsafe {
+        let offset =
+            arrays_offset(capacity, element_size) + which * capacity as usize * size_of::<u32>();
+        base.add(offset).cast::<u32>()
+    }
+}
+
+const ID_TO_INDEX: usize = 0;
+const INDEX_TO_ID: usize = 1;
+const GENERATION: usize = 2;
+const FREE_STACK: usize = 3;
+
+/// Compute total bytes needed in memory for a wide slot map.
+#[must_use]
+pub const fn layout_size32(capacity: u32, element_size: u32) -> usize {
+    arrays_offset(capacity, element_size) + 4 * capacity as usize * size_of::<u32>()
+}
+
+/// Get capacity from header
+/// # Safety
+/// `base` must point to a valid initialized wide slot map.
+#[must_use]
+pub const unsafe fn capacity32(base: *const u8) -> u32 {
+    unsafe { *base.cast::<u32>() }
+}
+
+/// Get current element count (len)
+/// # Safety
+/// `base` must point to a valid initialized wide slot map.
+#[must_use]
+pub const unsafe fn element_count32(base: *const u8) -> u32 {
+    unsafe { *base.add(4).cast::<u32>() }
+}
+
+/// Get element size from header
+/// # Safety
+/// `base` must point to a valid initialized wide slot map.
+#[must_use]
+pub const unsafe fn element_size32(base: *const u8) -> u32 {
+    unsafe { *base.add(8).cast::<u32>() }
+}
+
+/// Initialize a wide slot map.
+///
+/// # Safety
+/// - `base` must point to valid memory of at least `layout_size32(capacity, element_size)` bytes
+/// - `base` must be 4-byte aligned
+/// - `capacity` must be in `1..=MAX_CAPACITY32`
+pub unsafe fn init32(base: *mut u8, capacity: u32, element_size: u32) {
+    unsafe {
+        debug_assert_eq!((base as usize) & 3, 0, "base must be 4-byte aligned");
+        debug_assert!(
+            capacity != 0 && capacity <= MAX_CAPACITY32,
+            "capacity ({capacity}) must be in 1..={MAX_CAPACITY32}"
+        );
+
+        let cap = capacity as usize;
+
+        ptr::write(base.cast::<u32>(), capacity);
+        ptr::write(base.add(4).cast::<u32>(), 0); // len
+        ptr::write(base.add(8).cast::<u32>(), element_size);
+        ptr::write(base.add(12).cast::<u32>(), 0); // pad
+
+        let trailer_off = trailer_offset(capacity, element_size);
+        ptr::write(base.add(trailer_off).cast::<u32>(), WIDE_TRAILER_MAGIC);
+        ptr::write(base.add(trailer_off + 4).cast::<u32>(), element_size);
+        ptr::write(base.add(trailer_off + 8).cast::<u32>(), capacity); // free_top
+        ptr::write(base.add(trailer_off + 12).cast::<u32>(), 0); // pad
+
+        let id_to_idx_ptr = array_ptr(base, capacity, element_size, ID_TO_INDEX);
+        let idx_to_id_ptr = array_ptr(base, capacity, element_size, INDEX_TO_ID);
+        let gen_ptr = array_ptr(base, capacity, element_size, GENERATION);
+        let free_stk_ptr = array_ptr(base, capacity, element_size, FREE_STACK);
+        #[allow(clippy::cast_possible_truncation)]
+        for i in 0..cap {
+            ptr::write(id_to_idx_ptr.add(i), INVALID_U32);
+            ptr::write(idx_to_id_ptr.add(i), INVALID_U32);
+            ptr::write(gen_ptr.add(i), 1);
+            ptr::write(free_stk_ptr.add(i), i as u32);
+        }
+    }
+}
+
+/// Allocate a new ID and generation. Returns (id, generation) for the new handle.
+///
+/// # Safety
+/// `base` must point to a valid initialized wide slot map.
+pub unsafe fn allocate32(base: *mut u8) -> Option<(u32, u32)> {
+    unsafe {
+        debug_validate32(base);
+
+        let capacity = capacity32(base);
+        let element_size = element_size32(base);
+        let len_ptr = base.add(4).cast::<u32>();
+        let len = *len_ptr;
+
+        let free_top_p = base
+            .add(trailer_offset(capacity, element_size) + 8)
+            .cast::<u32>();
+        let free_top = *free_top_p;
+        if free_top == 0 {
+            return None;
+        }
+
+        let new_free_top = free_top - 1;
+        ptr::write(free_top_p, new_free_top);
+        let id = *array_ptr(base, capacity, element_size, FREE_STACK).add(new_free_top as usize);
+
+        let index = len;
+        ptr::write(len_ptr, len + 1);
+
+        ptr::write(
+            array_ptr(base, capacity, element_size, ID_TO_INDEX).add(id as usize),
+            index,
+        );
+        ptr::write(
+            array_ptr(base, capacity, element_size, INDEX_TO_ID).add(index as usize),
+            id,
+        );
+
+        let generation = *array_ptr(base, capacity, element_size, GENERATION).add(id as usize);
+        Some((id, generation))
+    }
+}
+
+/// Validate handle and get dense index
+unsafe fn validate_handle32(base: *mut u8, id: u32, generation: u32) -> Option<u32> {
+    unsafe {
+        let capacity = capacity32(base);
+        let element_size = element_size32(base);
+
+        if id >= capacity {
+            return None;
+        }
+        if *array_ptr(base, capacity, element_size, GENERATION).add(id as usize) != generation {
+            return None;
+        }
+
+        let index = *array_ptr(base, capacity, element_size, ID_TO_INDEX).add(id as usize);
+        if index == INVALID_U32 {
+            return None;
+        }
+
+        Some(index)
+    }
+}
+
+/// Check if a handle is alive. Out of range ids are reported as not alive.
+/// # Safety
+/// `base` must point to a valid initialized wide slot map.
+pub unsafe fn is_alive32(base: *mut u8, id: u32, generation: u32) -> bool {
+    unsafe {
+        debug_validate32(base);
+
+        validate_handle32(base, id, generation).is_some()
+    }
+}
+
+/// Get value pointer for a valid handle, or None if the handle is invalid.
+/// # Safety
+/// `base` must point to a valid initialized wide slot map.
+pub unsafe fn get_value_ptr32(base: *mut u8, id: u32, generation: u32) -> Option<*mut u8> {
+    unsafe {
+        debug_validate32(base);
+
+        let index = validate_handle32(base, id, generation)?;
+        let offset = VALUES_OFFSET + index as usize * element_size32(base) as usize;
+        Some(base.add(offset))
+    }
+}
+
+/// Insert raw bytes at handle (id, generation). Returns false if the handle is invalid.
+/// # Safety
+/// - `base` must point to a valid initialized wide slot map
+/// - `src` must be valid for reads of `element_size` bytes
+pub unsafe fn insert32(base: *mut u8, id: u32, generation: u32, src: *const u8) -> bool {
+    unsafe {
+        let Some(dst) = get_value_ptr32(base, id, generation) else {
+            return false;
+        };
+        ptr::copy_nonoverlapping(src, dst, element_size32(base) as usize);
+        true
+    }
+}
+
+/// Remove by handle; swap-removes in the dense area, bumps the generation and pushes the id
+/// on the free stack. Returns false if the handle is invalid.
+/// # Safety
+/// `base` must point to a valid initialized wide slot map.
+pub unsafe fn remove32(base: *mut u8, id: u32, generation: u32) -> bool {
+    unsafe {
+        debug_validate32(base);
+
+        let capacity = capacity32(base);
+        let element_size = element_size32(base);
+
+        let Some(index) = validate_handle32(base, id, generation) else {
+            return false;
+        };
+
+        let len_ptr = base.add(4).cast::<u32>();
+        let last = *len_ptr - 1;
+
+        let id_to_idx_ptr = array_ptr(base, capacity, element_size, ID_TO_INDEX);
+        let idx_to_id_ptr = array_ptr(base, capacity, element_size, INDEX_TO_ID);
+
+        if index != last {
+            let elem_size = element_size as usize;
+            ptr::swap_nonoverlapping(
+                base.add(VALUES_OFFSET + index as usize * elem_size),
+                base.add(VALUES_OFFSET + last as usize * elem_size),
+                elem_size,
+            );
+
+            let moved_id = *idx_to_id_ptr.add(last as usize);
+            ptr::write(idx_to_id_ptr.add(index as usize), moved_id);
+            ptr::write(id_to_idx_ptr.add(moved_id as usize), index);
+        }
+
+        ptr::write(idx_to_id_ptr.add(last as usize), INVALID_U32);
+        ptr::write(id_to_idx_ptr.add(id as usize), INVALID_U32);
+        ptr::write(len_ptr, last);
+
+        let gen_ptr = array_ptr(base, capacity, element_size, GENERATION);
+        let old_gen = *gen_ptr.add(id as usize);
+        // Generation 0 is never valid, so it is skipped on wrap
+        let new_gen = match old_gen.wrapping_add(1) {
+            0 => 1,
+            next => next,
+        };
+        ptr::write(gen_ptr.add(id as usize), new_gen);
+
+        let free_top_p = base
+            .add(trailer_offset(capacity, element_size) + 8)
+            .cast::<u32>();
+        let free_top = *free_top_p;
+        ptr::write(
+            array_ptr(base, capacity, element_size, FREE_STACK).add(free_top as usize),
+            id,
+        );
+        ptr::write(free_top_p, free_top + 1);
+
+        true
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+#![cfg(feature = "ffi")]
+
+use dense_slotmap_mem::ffi::{
+    dsm_allocate, dsm_element_count, dsm_init, dsm_insert, dsm_is_alive, dsm_layout_size,
+    dsm_remove, dsm_stride, dsm_values_base_ptr,
+};
+
+#[test]
+fn test_ffi_cycle() {
+    let capacity = 2u16;
+    let element_size = 4u32;
+    let size = dsm_layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        dsm_init(base, capacity, element_size);
+        assert_eq!(dsm_element_count(base), 0);
+
+        let (mut id, mut generation) = (0u16, 0u16);
+        assert!(dsm_allocate(base, &raw mut id, &raw mut generation));
+        assert!(dsm_is_alive(base, id, generation));
+        assert!(!dsm_is_alive(base, 100, generation));
+
+        let value = 0xDEAD_BEEFu32;
+        assert!(dsm_insert(
+            base,
+            id,
+            generation,
+            (&raw const value).cast::<u8>()
+        ));
+        assert_eq!(*dsm_values_base_ptr(base).cast::<u32>(), value);
+        assert_eq!(dsm_stride(element_size), 4);
+
+        let (mut other_id, mut other_generation) = (0u16, 0u16);
+        assert!(dsm_allocate(
+            base,
+            &raw mut other_id,
+            &raw mut other_generation
+        ));
+        assert!(!dsm_allocate(
+            base,
+            &raw mut other_id,
+            &raw mut other_generation
+        ));
+        assert_eq!(dsm_element_count(base), 2);
+
+        assert!(dsm_remove(base, id, generation));
+        assert!(!dsm_remove(base, id, generation));
+        assert!(!dsm_is_alive(base, id, generation));
+        assert!(!dsm_insert(
+            base,
+            id,
+            generation,
+            (&raw const value).cast::<u8>()
+        ));
+        assert_eq!(dsm_element_count(base), 1);
+    }
+}
@@ -18,7 +18,7 @@ fn test_layout_and_initialization() {
     let header = 8;
     let values_size = capacity as usize * element_size as usize;
     let trailer_off = ((header + values_size) + 3) & !3;
-    let trailer_header = 12;
+    let trailer_header = 16;
     let arrays_off = ((trailer_off + trailer_header) + 3) & !3;
     let arrays_size = 4 * capacity as usize * size_of::<u16>();
     assert_eq!(size, arrays_off + arrays_size);
@@ -363,7 +363,7 @@ fn test_debug_validation_corrupted_magic() {
 
     unsafe {
         init(base, capacity, element_size);
-        let trailer_off = 8 + ((capacity as usize * element_size as usize) + 3) & !3;
+        let trailer_off = (8 + (capacity as usize * element_size as usize) + 3) & !3;
         *(base.add(trailer_off) as *mut u32) = 0xDEADBEEF;
         let _ = allocate(base); // Should panic
     }
@@ -381,7 +381,7 @@ fn test_debug_validation_element_size_mismatch() {
 
     unsafe {
         init(base, capacity, element_size);
-        let trailer_off = 8 + ((capacity as usize * element_size as usize) + 3) & !3;
+        let trailer_off = (8 + (capacity as usize * element_size as usize) + 3) & !3;
         *(base.add(trailer_off + 4) as *mut u32) = 999;
         let _ = allocate(base); // Should panic
     }
@@ -416,7 +416,7 @@ fn test_debug_validation_invalid_free_top() {
 
     unsafe {
         init(base, capacity, element_size);
-        let trailer_off = 8 + ((capacity as usize * element_size as usize) + 3) & !3;
+        let trailer_off = (8 + (capacity as usize * element_size as usize) + 3) & !3;
         *(base.add(trailer_off + 8) as *mut u16) = capacity + 10;
         let _ = allocate(base); // Should panic
     }
@@ -439,3 +439,2790 @@ fn test_debug_validation_invariant_violation() {
         let _ = is_alive(base, 0, 1); // Should panic
     }
 }
+
+#[test]
+fn test_count_where() {
+    use dense_slotmap_mem::count_where;
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in 0..7u32 {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation));
+        }
+
+        let is_even = |ptr: *const u8| (*(ptr as *const u32)).is_multiple_of(2);
+        assert_eq!(count_where(base, is_even), 4, "0, 2, 4, 6 are even");
+
+        // Remove value 2 (swap-remove moves 6 into its slot)
+        remove(base, handles[2].0, handles[2].1);
+        assert_eq!(count_where(base, is_even), 3);
+        assert_eq!(count_where(base, |_| true), element_count(base));
+    }
+}
+
+#[test]
+fn test_allocate_all() {
+    use dense_slotmap_mem::allocate_all;
+
+    let capacity = 6u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        // Take two slots first and retire one, so the free stack is not pristine
+        let (first_id, first_generation) = allocate(base).unwrap();
+        let (second_id, second_generation) = allocate(base).unwrap();
+        remove(base, first_id, first_generation);
+
+        let mut handles = [(0u16, 0u16); 6];
+        let count = allocate_all(base, &mut handles);
+        assert_eq!(count, capacity - 1);
+        assert_eq!(element_count(base), capacity);
+        assert!(allocate(base).is_none(), "Should be full");
+
+        // LIFO reuse: the retired ID comes back first with a bumped generation
+        assert_eq!(handles[0], (first_id, first_generation + 1));
+
+        for (i, &(id, generation)) in handles[..count as usize].iter().enumerate() {
+            assert!(is_alive(base, id, generation));
+            let value = i as u32 * 10;
+            assert!(insert(
+                base,
+                id,
+                generation,
+                (&raw const value).cast::<u8>()
+            ));
+        }
+        assert!(is_alive(base, second_id, second_generation));
+
+        // Selectively remove after preallocation
+        let (id, generation) = handles[2];
+        assert!(remove(base, id, generation));
+        assert_eq!(element_count(base), capacity - 1);
+        assert_eq!(allocate_all(base, &mut handles), 1);
+    }
+}
+
+#[test]
+fn test_sort_handles_by_locality() {
+    use dense_slotmap_mem::{dense_index_of_id, sort_handles_by_locality};
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for _ in 0..6 {
+            handles.push(allocate(base).unwrap());
+        }
+
+        // Swap-remove shuffles the dense order, and leaves one dead handle in the list
+        let dead = handles[1];
+        remove(base, dead.0, dead.1);
+        assert_eq!(dense_index_of_id(base, dead.0), None);
+        assert_eq!(dense_index_of_id(base, capacity), None);
+
+        handles.reverse();
+        sort_handles_by_locality(base, &mut handles);
+
+        assert_eq!(*handles.last().unwrap(), dead, "Dead handles go last");
+        let live = &handles[..handles.len() - 1];
+        for (expected_index, &(id, generation)) in live.iter().enumerate() {
+            assert!(is_alive(base, id, generation));
+            assert_eq!(dense_index_of_id(base, id), Some(expected_index as u16));
+        }
+    }
+}
+
+#[test]
+fn test_insert_with_and_allocate_with_closure() {
+    use dense_slotmap_mem::{allocate_with_closure, get_value_ptr, insert_with};
+
+    let capacity = 2u16;
+    let element_size = 8u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let (id, generation) = allocate(base).unwrap();
+        assert!(insert_with(base, id, generation, |ptr| {
+            *(ptr as *mut u64) = 0xDEAD_BEEF;
+        }));
+        let stored = *(get_value_ptr(base, id, generation).unwrap() as *const u64);
+        assert_eq!(stored, 0xDEAD_BEEF);
+
+        let (other_id, other_generation) =
+            allocate_with_closure(base, |ptr| *(ptr as *mut u64) = 77).unwrap();
+        let stored = *(get_value_ptr(base, other_id, other_generation).unwrap() as *const u64);
+        assert_eq!(stored, 77);
+
+        // Full map must not call the closure
+        let mut called = false;
+        assert!(allocate_with_closure(base, |_| called = true).is_none());
+        assert!(!called);
+
+        // Stale handle must not call the closure
+        remove(base, id, generation);
+        assert!(!insert_with(base, id, generation, |_| called = true));
+        assert!(!called);
+    }
+}
+
+#[test]
+fn test_error_display() {
+    use dense_slotmap_mem::SlotMapError;
+
+    let error = SlotMapError::BufferTooSmall {
+        required: 64,
+        actual: 16,
+    };
+    assert_eq!(
+        error.to_string(),
+        "buffer too small: 16 bytes, layout requires 64"
+    );
+    assert_eq!(SlotMapError::Full.to_string(), "slot map is full");
+    assert_eq!(
+        SlotMapError::Corrupt {
+            reason: "bad trailer magic"
+        }
+        .to_string(),
+        "slot map is corrupt: bad trailer magic"
+    );
+
+    // Usable as a boxed std error for `?` propagation
+    let boxed: Box<dyn std::error::Error> = Box::new(SlotMapError::ZeroCapacity);
+    assert_eq!(boxed.to_string(), "capacity must not be 0");
+}
+
+#[test]
+fn test_handle_packing_round_trip() {
+    use dense_slotmap_mem::Handle;
+
+    let handle = Handle::new(0x1234, 0xABCD);
+    assert_eq!(handle.id(), 0x1234);
+    assert_eq!(handle.generation(), 0xABCD);
+    assert_eq!(handle.to_bits(), 0xABCD_1234, "id low, generation high");
+    assert_eq!(Handle::from_bits(handle.to_bits()), handle);
+
+    let (id, generation): (u16, u16) = handle.into();
+    assert_eq!(Handle::from((id, generation)), handle);
+
+    for &(id, generation) in &[(0, 1), (u16::MAX, u16::MAX), (7, 0)] {
+        let handle = Handle::new(id, generation);
+        assert_eq!((handle.id(), handle.generation()), (id, generation));
+    }
+
+    assert!(Handle::NULL.is_null());
+    assert_eq!(Handle::NULL.to_bits(), 0);
+    assert!(!Handle::new(0, 1).is_null());
+}
+
+#[test]
+fn test_handle_api() {
+    use dense_slotmap_mem::{
+        Handle, allocate_handle, get_value_ptr_handle, is_alive_handle, remove_handle,
+    };
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for i in 0..capacity {
+            let handle = allocate_handle(base).unwrap();
+            assert_ne!(handle, Handle::NULL, "NULL must never be allocated");
+            assert!(is_alive(base, handle.id(), handle.generation()));
+            let value = u32::from(i) * 3;
+            insert(
+                base,
+                handle.id(),
+                handle.generation(),
+                (&raw const value).cast::<u8>(),
+            );
+            handles.push(handle);
+        }
+        assert!(allocate_handle(base).is_none());
+
+        assert!(!is_alive_handle(base, Handle::NULL));
+        assert!(!is_alive_handle(base, Handle::new(capacity, 1)));
+
+        let handle = handles[1];
+        let stored = *(get_value_ptr_handle(base, handle).unwrap() as *const u32);
+        assert_eq!(stored, 3);
+
+        assert!(remove_handle(base, handle));
+        assert!(!is_alive_handle(base, handle));
+        assert!(get_value_ptr_handle(base, handle).is_none());
+        assert!(!remove_handle(base, handle));
+
+        // Packed handle survives a round trip through its wire representation
+        let wire = handles[2].to_bits();
+        assert!(is_alive_handle(base, Handle::from_bits(wire)));
+    }
+}
+
+#[test]
+fn test_iter_after_swap_removes() {
+    use dense_slotmap_mem::iter;
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for i in 0..6u32 {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const i).cast::<u8>());
+            handles.push((id, generation));
+        }
+        remove(base, handles[0].0, handles[0].1);
+        remove(base, handles[3].0, handles[3].1);
+        allocate(base).unwrap();
+
+        let slots = iter(base);
+        assert_eq!(slots.len(), element_count(base) as usize);
+
+        let mut seen_ptrs = Vec::new();
+        for (id, generation, value_ptr) in iter(base) {
+            assert!(is_alive(base, id, generation));
+            assert!(
+                !seen_ptrs.contains(&value_ptr),
+                "Value pointers must be distinct"
+            );
+            seen_ptrs.push(value_ptr);
+        }
+        assert_eq!(seen_ptrs.len(), 5);
+
+        // Values of the surviving original handles are reachable through the iterator
+        let mut values: Vec<u32> = iter(base)
+            .filter(|&(id, generation, _)| handles.contains(&(id, generation)))
+            .map(|(_, _, ptr)| *(ptr as *const u32))
+            .collect();
+        values.sort_unstable();
+        assert_eq!(values, [1, 2, 4, 5]);
+    }
+}
+
+#[test]
+fn test_get_value_ptr_by_index() {
+    use dense_slotmap_mem::get_value_ptr_by_index;
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in [10u32, 20, 30, 40, 50] {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation));
+        }
+
+        // Remove the middle element; the last one (50) is swapped into dense index 2
+        remove(base, handles[2].0, handles[2].1);
+
+        let swapped = *(get_value_ptr_by_index(base, 2).unwrap() as *const u32);
+        assert_eq!(swapped, 50);
+        let first = *(get_value_ptr_by_index(base, 0).unwrap() as *const u32);
+        assert_eq!(first, 10);
+
+        assert!(get_value_ptr_by_index(base, 3).is_some());
+        assert!(get_value_ptr_by_index(base, 4).is_none(), "index == len");
+        assert!(get_value_ptr_by_index(base, capacity).is_none());
+    }
+}
+
+#[test]
+fn test_grow_preserves_handles() {
+    use dense_slotmap_mem::{get_value_ptr, grow};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let mut src_buffer = vec![0u32; layout_size(capacity, element_size).div_ceil(4)];
+    let src = src_buffer.as_mut_ptr().cast::<u8>();
+
+    let dst_capacity = 10u16;
+    let mut dst_buffer = vec![0u32; layout_size(dst_capacity, element_size).div_ceil(4)];
+    let dst = dst_buffer.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(src, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in [11u32, 22, 33, 44] {
+            let (id, generation) = allocate(src).unwrap();
+            insert(src, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation, value));
+        }
+        // Retire one handle so a bumped generation and a non-trivial free stack are carried over
+        let (dead_id, dead_generation, _) = handles.remove(1);
+        remove(src, dead_id, dead_generation);
+
+        // A smaller destination is refused instead of writing past it
+        assert!(!grow(dst, capacity - 1, src));
+        assert_eq!(*(dst as *const u16), 0);
+        assert!(grow(dst, dst_capacity, src));
+
+        assert_eq!(*(dst as *const u16), dst_capacity);
+        assert_eq!(element_count(dst), 3);
+        for &(id, generation, value) in &handles {
+            assert!(is_alive(dst, id, generation));
+            let stored = *(get_value_ptr(dst, id, generation).unwrap() as *const u32);
+            assert_eq!(stored, value);
+        }
+        assert!(!is_alive(dst, dead_id, dead_generation));
+
+        // The previously freed ID is reused first, then the new high IDs
+        let (reused_id, reused_generation) = allocate(dst).unwrap();
+        assert_eq!(reused_id, dead_id);
+        assert_eq!(reused_generation, dead_generation + 1);
+
+        let mut new_ids = Vec::new();
+        while let Some((id, generation)) = allocate(dst) {
+            assert_eq!(generation, 1);
+            new_ids.push(id);
+        }
+        new_ids.sort_unstable();
+        assert_eq!(new_ids, (capacity..dst_capacity).collect::<Vec<_>>());
+        assert_eq!(element_count(dst), dst_capacity);
+    }
+}
+
+#[test]
+fn test_try_validate_variants() {
+    use dense_slotmap_mem::{ValidationError, try_validate};
+
+    let capacity = 3u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let trailer_off = (8 + (capacity as usize * element_size as usize) + 3) & !3;
+
+    let fresh = || {
+        let mut words = vec![0u32; size.div_ceil(4)];
+        unsafe { init(words.as_mut_ptr().cast::<u8>(), capacity, element_size) };
+        words
+    };
+
+    let mut words = fresh();
+    let base = words.as_mut_ptr().cast::<u8>();
+    assert_eq!(try_validate(base, size), Ok(()));
+
+    unsafe {
+        assert_eq!(
+            try_validate(base.add(2), size - 2),
+            Err(ValidationError::BadAlignment)
+        );
+        assert_eq!(
+            try_validate(base, 4),
+            Err(ValidationError::BufferTooSmall {
+                required: 8,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            try_validate(base, size - 1),
+            Err(ValidationError::BufferTooSmall {
+                required: size,
+                actual: size - 1
+            })
+        );
+
+        let mut words = fresh();
+        let base = words.as_mut_ptr().cast::<u8>();
+        *(base as *mut u16) = 0;
+        assert_eq!(try_validate(base, size), Err(ValidationError::ZeroCapacity));
+
+        let mut words = fresh();
+        let base = words.as_mut_ptr().cast::<u8>();
+        *(base.add(2) as *mut u16) = capacity + 1;
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::LenExceedsCapacity {
+                len: capacity + 1,
+                capacity
+            })
+        );
+
+        let mut words = fresh();
+        let base = words.as_mut_ptr().cast::<u8>();
+        *(base.add(trailer_off) as *mut u32) = 0xDEAD_BEEF;
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::BadTrailerMagic { found: 0xDEAD_BEEF })
+        );
+
+        let mut words = fresh();
+        let base = words.as_mut_ptr().cast::<u8>();
+        *(base.add(trailer_off + 4) as *mut u32) = 999;
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::ElemSizeMismatch {
+                header: element_size,
+                trailer: 999
+            })
+        );
+
+        let mut words = fresh();
+        let base = words.as_mut_ptr().cast::<u8>();
+        *(base.add(trailer_off + 8) as *mut u16) = capacity + 10;
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::FreeTopExceedsCapacity {
+                free_top: capacity + 10,
+                capacity
+            })
+        );
+
+        let mut words = fresh();
+        let base = words.as_mut_ptr().cast::<u8>();
+        *(base.add(2) as *mut u16) = 2;
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::InvariantViolated {
+                len: 2,
+                free_top: capacity,
+                capacity
+            })
+        );
+    }
+}
+
+#[test]
+fn test_try_validate_array_variants() {
+    use dense_slotmap_mem::{ValidationError, layout_offsets, try_validate};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let offsets = layout_offsets(capacity, element_size);
+
+    // Each map has `allocated` ids handed out before the arrays are corrupted
+    let fresh = |allocated: usize| {
+        let mut words = vec![0u32; size.div_ceil(4)];
+        let base = words.as_mut_ptr().cast::<u8>();
+        let mut ids = Vec::new();
+        unsafe {
+            init(base, capacity, element_size);
+            for _ in 0..allocated {
+                ids.push(allocate(base).unwrap().0);
+            }
+        }
+        (words, ids)
+    };
+    let array = |base: *mut u8, offset: usize| unsafe { base.add(offset).cast::<u16>() };
+
+    unsafe {
+        let (mut words, _) = fresh(0);
+        let base = words.as_mut_ptr().cast::<u8>();
+        *array(base, offsets.free_stack).add(3) = 60;
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::FreeIdOutOfRange { slot: 3, id: 60 })
+        );
+
+        let (mut words, ids) = fresh(1);
+        let base = words.as_mut_ptr().cast::<u8>();
+        *array(base, offsets.free_stack) = ids[0];
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::FreeIdOccupied {
+                slot: 0,
+                id: ids[0]
+            })
+        );
+
+        let (mut words, ids) = fresh(1);
+        let base = words.as_mut_ptr().cast::<u8>();
+        *array(base, offsets.id_to_index).add(ids[0] as usize) = 2;
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::IndexOutOfRange {
+                id: ids[0],
+                index: 2,
+                len: 1
+            })
+        );
+
+        let (mut words, ids) = fresh(2);
+        let base = words.as_mut_ptr().cast::<u8>();
+        *array(base, offsets.index_to_id) = ids[1];
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::MappingMismatch {
+                id: ids[0],
+                index: 0,
+                back: ids[1]
+            })
+        );
+
+        let (mut words, ids) = fresh(1);
+        let base = words.as_mut_ptr().cast::<u8>();
+        *array(base, offsets.id_to_index).add(ids[0] as usize) = 0xFFFF;
+        assert_eq!(
+            try_validate(base, size),
+            Err(ValidationError::OccupiedCountMismatch {
+                occupied: 0,
+                len: 1
+            })
+        );
+    }
+}
+
+#[test]
+fn test_try_validate_huge_element_size_is_bounded_by_buffer_len() {
+    use dense_slotmap_mem::{ValidationError, try_validate};
+
+    // A small buffer claiming a huge layout must be rejected before reading the trailer
+    let mut words = [0u32; 4];
+    let base = words.as_mut_ptr().cast::<u8>();
+    unsafe {
+        *(base as *mut u16) = 1000;
+        *(base.add(4) as *mut u32) = 1024 * 1024;
+    }
+    assert!(matches!(
+        try_validate(base, 16),
+        Err(ValidationError::BufferTooSmall { actual: 16, .. })
+    ));
+}
+
+#[test]
+fn test_try_validate_rejects_previous_layout_magic() {
+    use dense_slotmap_mem::{ValidationError, layout_offsets, try_validate};
+
+    // A 0.1.x buffer has the 'SVEC' magic and its arrays 4 bytes earlier
+    let capacity = 3u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+    unsafe {
+        init(base, capacity, element_size);
+        let trailer_off = layout_offsets(capacity, element_size).trailer_offset;
+        *(base.add(trailer_off) as *mut u32) = 0x5356_4543;
+    }
+    assert_eq!(
+        try_validate(base, size),
+        Err(ValidationError::BadTrailerMagic { found: 0x5356_4543 })
+    );
+}
+
+#[test]
+fn test_try_validate_max_header_layout_does_not_wrap() {
+    use dense_slotmap_mem::{ValidationError, try_validate};
+
+    // u16::MAX * u32::MAX overflows a 32-bit usize, which must not wrap to a small size
+    let mut words = [0u32; 4];
+    let base = words.as_mut_ptr().cast::<u8>();
+    unsafe {
+        *(base as *mut u16) = u16::MAX;
+        *(base.add(4) as *mut u32) = u32::MAX;
+    }
+    let required = if cfg!(target_pointer_width = "64") {
+        layout_size(u16::MAX, u32::MAX)
+    } else {
+        usize::MAX
+    };
+    assert_eq!(
+        try_validate(base, 16),
+        Err(ValidationError::BufferTooSmall {
+            required,
+            actual: 16
+        })
+    );
+}
+
+#[test]
+fn test_portable_round_trip() {
+    use dense_slotmap_mem::{from_portable, get_value_ptr, to_portable, try_validate};
+
+    let capacity = 5u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut src_words = vec![0u32; size.div_ceil(4)];
+    let src = src_words.as_mut_ptr().cast::<u8>();
+
+    let mut handles = Vec::new();
+    unsafe {
+        init(src, capacity, element_size);
+        for value in [0x0102_0304u32, 0x0A0B_0C0D, 0xCAFE_F00D] {
+            let (id, generation) = allocate(src).unwrap();
+            insert(src, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation, value));
+        }
+        let (id, generation, _) = handles.remove(0);
+        remove(src, id, generation);
+    }
+
+    let mut portable = vec![0u8; size];
+    unsafe { to_portable(src, &mut portable) };
+
+    // Header fields are little-endian regardless of the host
+    assert_eq!(portable[0..2], capacity.to_le_bytes());
+    assert_eq!(portable[2..4], 2u16.to_le_bytes());
+    assert_eq!(portable[4..8], element_size.to_le_bytes());
+    let trailer_off = (8 + (capacity as usize * element_size as usize) + 3) & !3;
+    assert_eq!(
+        portable[trailer_off..trailer_off + 4],
+        0x5356_4532u32.to_le_bytes()
+    );
+
+    let mut dst_words = vec![0u32; size.div_ceil(4)];
+    let dst = dst_words.as_mut_ptr().cast::<u8>();
+    unsafe { from_portable(dst, &portable) };
+
+    assert_eq!(try_validate(dst, size), Ok(()));
+    assert_eq!(src_words, dst_words, "Round trip must be lossless");
+    unsafe {
+        assert_eq!(element_count(dst), 2);
+        for &(id, generation, value) in &handles {
+            assert!(is_alive(dst, id, generation));
+            let stored = *(get_value_ptr(dst, id, generation).unwrap() as *const u32);
+            assert_eq!(stored, value);
+        }
+    }
+}
+
+#[test]
+fn test_from_portable_converts_little_endian_fields() {
+    use dense_slotmap_mem::{from_portable, try_validate};
+
+    // Build the canonical form by hand, as a peer of any endianness would produce it
+    let capacity = 2u16;
+    let element_size = 1u32;
+    let size = layout_size(capacity, element_size);
+    let trailer_off = (8 + (capacity as usize * element_size as usize) + 3) & !3;
+    let arrays_off = (trailer_off + 16 + 3) & !3;
+
+    let mut portable = vec![0u8; size];
+    let mut put_u16 = |offset: usize, value: u16| {
+        portable[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    };
+    put_u16(0, capacity);
+    put_u16(2, 0);
+    put_u16(trailer_off + 8, capacity);
+    for i in 0..capacity as usize {
+        put_u16(arrays_off + i * 2, 0xFFFF);
+        put_u16(arrays_off + (capacity as usize + i) * 2, 0xFFFF);
+        put_u16(arrays_off + (2 * capacity as usize + i) * 2, 1);
+        put_u16(arrays_off + (3 * capacity as usize + i) * 2, i as u16);
+    }
+    portable[4..8].copy_from_slice(&element_size.to_le_bytes());
+    portable[trailer_off..trailer_off + 4].copy_from_slice(&0x5356_4532u32.to_le_bytes());
+    portable[trailer_off + 4..trailer_off + 8].copy_from_slice(&element_size.to_le_bytes());
+
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+    unsafe { from_portable(base, &portable) };
+
+    assert_eq!(try_validate(base, size), Ok(()));
+    unsafe {
+        let (id, generation) = allocate(base).unwrap();
+        assert_eq!((id, generation), (1, 1));
+        assert!(is_alive(base, id, generation));
+    }
+}
+
+#[test]
+fn test_capacity_free_count_accessors() {
+    use dense_slotmap_mem::{capacity, free_count, is_empty, is_full};
+
+    let cap = 5u16;
+    let element_size = 4u32;
+    let size = layout_size(cap, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, cap, element_size);
+        assert_eq!(capacity(base), cap);
+        assert_eq!(free_count(base), cap);
+        assert!(is_empty(base));
+        assert!(!is_full(base));
+
+        let check = || {
+            assert_eq!(capacity(base), element_count(base) + free_count(base));
+        };
+
+        let mut handles = Vec::new();
+        for _ in 0..cap {
+            handles.push(allocate(base).unwrap());
+            check();
+        }
+        assert!(is_full(base));
+        assert!(!is_empty(base));
+        assert_eq!(free_count(base), 0);
+
+        for &(id, generation) in handles.iter().step_by(2) {
+            remove(base, id, generation);
+            check();
+        }
+        assert!(!is_full(base));
+        allocate(base).unwrap();
+        check();
+
+        clear(base);
+        check();
+        assert!(is_empty(base));
+        assert_eq!(free_count(base), cap);
+    }
+}
+
+#[test]
+fn test_allocate_and_insert() {
+    use dense_slotmap_mem::{allocate_and_insert, get_value_ptr};
+
+    let capacity = 3u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in [7u32, 8, 9] {
+            let (id, generation) = allocate_and_insert(base, (&raw const value).cast::<u8>())
+                .expect("should allocate");
+            assert!(is_alive(base, id, generation));
+            handles.push((id, generation, value));
+        }
+        for &(id, generation, value) in &handles {
+            let stored = *(get_value_ptr(base, id, generation).unwrap() as *const u32);
+            assert_eq!(stored, value);
+        }
+
+        // A full map must not touch `src` (a null pointer would fault if it did)
+        assert!(allocate_and_insert(base, core::ptr::null()).is_none());
+        assert_eq!(element_count(base), capacity);
+    }
+}
+
+#[test]
+fn test_remove_and_zero() {
+    use dense_slotmap_mem::{get_value_ptr, remove_and_zero};
+
+    let capacity = 4u16;
+    let element_size = 8u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for key in [
+            0x1111_1111_1111_1111u64,
+            0x2222_2222_2222_2222,
+            0x3333_3333_3333_3333,
+        ] {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const key).cast::<u8>());
+            handles.push((id, generation));
+        }
+
+        // Remove the first: the last element moves to index 0, the tail slot (index 2) is zeroed
+        assert!(remove_and_zero(base, handles[0].0, handles[0].1));
+        let tail = std::slice::from_raw_parts(base.add(values_offset(base) + 2 * 8), 8);
+        assert_eq!(tail, [0u8; 8]);
+        let moved = *(get_value_ptr(base, handles[2].0, handles[2].1).unwrap() as *const u64);
+        assert_eq!(moved, 0x3333_3333_3333_3333);
+
+        // Remove the moved element: the remaining one moves to index 0, index 1 is zeroed
+        assert!(remove_and_zero(base, handles[2].0, handles[2].1));
+        let tail = std::slice::from_raw_parts(base.add(values_offset(base) + 8), 8);
+        assert_eq!(tail, [0u8; 8]);
+        let remaining = *(get_value_ptr(base, handles[1].0, handles[1].1).unwrap() as *const u64);
+        assert_eq!(remaining, 0x2222_2222_2222_2222);
+
+        assert!(!remove_and_zero(base, handles[2].0, handles[2].1));
+        assert_eq!(element_count(base), 1);
+    }
+}
+
+#[test]
+fn test_clear_zeroed() {
+    use dense_slotmap_mem::clear_zeroed;
+
+    let capacity = 3u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+        for _ in 0..capacity {
+            let (id, generation) = allocate(base).unwrap();
+            let value = 0xFFFF_FFFFu32;
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+        }
+
+        clear_zeroed(base);
+        assert_eq!(element_count(base), 0);
+        let values = std::slice::from_raw_parts(base.add(values_offset(base)), 12);
+        assert!(values.iter().all(|&byte| byte == 0));
+        assert!(allocate(base).is_some());
+    }
+}
+
+#[test]
+fn test_aligned_layout() {
+    use dense_slotmap_mem::{
+        aligned_element_size, element_align, element_size, get_value_ptr, init_aligned,
+        layout_size_aligned, try_validate,
+    };
+
+    #[repr(C, align(8))]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Pair {
+        a: f64,
+        b: f64,
+    }
+
+    #[repr(C, align(16))]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Wide {
+        lanes: [f32; 4],
+        tag: u32,
+    }
+
+    assert_eq!(aligned_element_size(12, 8), 16);
+    assert_eq!(aligned_element_size(16, 8), 16);
+    assert_eq!(layout_size_aligned(4, 16, 8), layout_size(4, 16));
+    assert_eq!(layout_size_aligned(4, 16, 16), 8 + layout_size(4, 16));
+
+    fn exercise<T: Copy + PartialEq + core::fmt::Debug>(make: impl Fn(u32) -> T) {
+        let capacity = 5u16;
+        let align = align_of::<T>() as u16;
+        let size = layout_size_aligned(capacity, size_of::<T>() as u32, align);
+
+        // Over-allocate and align the buffer start by hand, as a custom allocator would
+        let mut storage = vec![0u8; size + align as usize];
+        let offset = storage.as_ptr().align_offset(align as usize);
+        let buffer = unsafe { storage.as_mut_ptr().add(offset) };
+
+        unsafe {
+            let base = init_aligned(buffer, capacity, size_of::<T>() as u32, align);
+            let buffer_len = size - base.offset_from(buffer) as usize;
+            assert_eq!(try_validate(base, buffer_len), Ok(()));
+            assert_eq!(element_align(base), align);
+            assert_eq!(element_size(base) as usize % align as usize, 0);
+
+            let mut handles = Vec::new();
+            for i in 0..capacity as u32 {
+                let (id, generation) = allocate(base).unwrap();
+                let value = make(i);
+                insert(base, id, generation, (&raw const value).cast::<u8>());
+                handles.push((id, generation, value));
+            }
+            remove(base, handles[1].0, handles[1].1);
+            handles.remove(1);
+
+            for &(id, generation, value) in &handles {
+                let ptr = get_value_ptr(base, id, generation).unwrap();
+                assert_eq!(ptr as usize % align as usize, 0, "slot must be aligned");
+                assert_eq!(*ptr.cast::<T>(), value);
+            }
+        }
+    }
+
+    exercise(|i| Pair {
+        a: f64::from(i),
+        b: -f64::from(i),
+    });
+    exercise(|i| Wide {
+        lanes: [i as f32; 4],
+        tag: i,
+    });
+}
+
+#[test]
+fn test_try_validate_element_align() {
+    use dense_slotmap_mem::{
+        ValidationError, element_align, init_aligned, layout_offsets, layout_size_aligned,
+        try_validate,
+    };
+
+    let capacity = 3u16;
+    let element_size = 16u32;
+    let align = 16u16;
+    let size = layout_size_aligned(capacity, element_size, align);
+    let trailer_off = layout_offsets(capacity, element_size).trailer_offset;
+
+    // Room for two 16-aligned copies of the layout
+    let mut storage = vec![0u8; 2 * size + 2 * align as usize];
+    let offset = storage.as_ptr().align_offset(align as usize);
+    let buffer = unsafe { storage.as_mut_ptr().add(offset) };
+    let other = unsafe { buffer.add(size.next_multiple_of(align as usize)) };
+
+    unsafe {
+        let base = init_aligned(buffer, capacity, element_size, align);
+        let len = size - base.offset_from(buffer) as usize;
+        assert_eq!(try_validate(base, len), Ok(()));
+
+        // The same bytes at the start of a 16-aligned buffer put the values 8 bytes off
+        core::ptr::copy_nonoverlapping(base, other, len);
+        assert_eq!(
+            try_validate(other, len),
+            Err(ValidationError::ValuesMisaligned { element_align: 16 })
+        );
+
+        for bad in [2u16, 3, 24] {
+            *(base.add(trailer_off + 10) as *mut u16) = bad;
+            assert_eq!(
+                try_validate(base, len),
+                Err(ValidationError::BadElementAlign { element_align: bad })
+            );
+        }
+
+        // Alignments below 4 are recorded as 4 and validate at any 4-byte aligned base
+        let small = init_aligned(buffer, capacity, element_size, 2);
+        assert_eq!(element_align(small), 4);
+        assert_eq!(
+            try_validate(small, layout_size_aligned(capacity, element_size, 2)),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn test_swap() {
+    use dense_slotmap_mem::{dense_index_of_id, get_value_ptr, get_value_ptr_by_index, swap};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in [100u32, 200, 300] {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation));
+        }
+        let (a_id, a_gen) = handles[0];
+        let (c_id, c_gen) = handles[2];
+
+        assert!(swap(base, a_id, a_gen, c_id, c_gen));
+
+        // Handles still resolve to their own values, with unchanged generations
+        assert!(is_alive(base, a_id, a_gen));
+        assert!(is_alive(base, c_id, c_gen));
+        assert_eq!(
+            *(get_value_ptr(base, a_id, a_gen).unwrap() as *const u32),
+            100
+        );
+        assert_eq!(
+            *(get_value_ptr(base, c_id, c_gen).unwrap() as *const u32),
+            300
+        );
+
+        // ...but their dense positions have traded places
+        assert_eq!(dense_index_of_id(base, a_id), Some(2));
+        assert_eq!(dense_index_of_id(base, c_id), Some(0));
+        assert_eq!(
+            *(get_value_ptr_by_index(base, 0).unwrap() as *const u32),
+            300
+        );
+        assert_eq!(
+            *(get_value_ptr_by_index(base, 2).unwrap() as *const u32),
+            100
+        );
+
+        // Same id, or any invalid handle, is rejected
+        assert!(!swap(base, a_id, a_gen, a_id, a_gen));
+        assert!(!swap(base, a_id, a_gen + 1, c_id, c_gen));
+        remove(base, c_id, c_gen);
+        assert!(!swap(base, a_id, a_gen, c_id, c_gen));
+
+        // Mappings stay consistent for swap-remove after a swap
+        assert_eq!(
+            *(get_value_ptr(base, a_id, a_gen).unwrap() as *const u32),
+            100
+        );
+        let (b_id, b_gen) = handles[1];
+        assert_eq!(
+            *(get_value_ptr(base, b_id, b_gen).unwrap() as *const u32),
+            200
+        );
+    }
+}
+
+#[test]
+fn test_handle_for_index() {
+    use dense_slotmap_mem::handle_for_index;
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for _ in 0..6 {
+            handles.push(allocate(base).unwrap());
+        }
+        remove(base, handles[0].0, handles[0].1);
+        remove(base, handles[4].0, handles[4].1);
+        let reallocated = allocate(base).unwrap();
+
+        let mut recovered = Vec::new();
+        for index in 0..element_count(base) {
+            let (id, generation) = handle_for_index(base, index).expect("index < len");
+            assert!(is_alive(base, id, generation));
+            recovered.push((id, generation));
+        }
+        assert!(handle_for_index(base, element_count(base)).is_none());
+
+        let mut expected = vec![handles[1], handles[2], handles[3], handles[5], reallocated];
+        expected.sort_unstable();
+        recovered.sort_unstable();
+        assert_eq!(recovered, expected);
+    }
+}
+
+#[test]
+fn test_checksum_detects_single_byte_flip() {
+    use dense_slotmap_mem::{update_checksum, verify_checksum};
+
+    let capacity = 5u16;
+    let element_size = 6u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        for i in 0..3u8 {
+            let (id, generation) = allocate(base).unwrap();
+            let value = [i; 6];
+            insert(base, id, generation, value.as_ptr());
+        }
+
+        update_checksum(base);
+        assert!(verify_checksum(base, size));
+
+        // Header, values, padding, trailer and arrays are all covered
+        for offset in 0..size {
+            let byte = base.add(offset);
+            *byte ^= 0x01;
+            assert!(
+                !verify_checksum(base, size),
+                "flip at offset {offset} not detected"
+            );
+            *byte ^= 0x01;
+        }
+        assert!(verify_checksum(base, size));
+    }
+}
+
+#[test]
+fn test_checksum_survives_portable_round_trip() {
+    use dense_slotmap_mem::{from_portable, to_portable, update_checksum, verify_checksum};
+
+    let capacity = 3u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        allocate(base).unwrap();
+        update_checksum(base);
+    }
+
+    let mut portable = vec![0u8; size];
+    let mut copy = vec![0u32; size.div_ceil(4)];
+    unsafe {
+        to_portable(base, &mut portable);
+        from_portable(copy.as_mut_ptr().cast::<u8>(), &portable);
+    }
+    assert!(verify_checksum(copy.as_ptr().cast::<u8>(), size));
+}
+
+#[test]
+#[cfg(feature = "checksum")]
+fn test_checksum_feature_refreshes_on_mutation() {
+    use dense_slotmap_mem::{for_each, verify_checksum};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        assert!(verify_checksum(base, size));
+
+        let (id, generation) = allocate(base).unwrap();
+        assert!(verify_checksum(base, size));
+        let value = 99u32;
+        insert(base, id, generation, (&raw const value).cast::<u8>());
+        assert!(verify_checksum(base, size));
+        for_each(base, |_, _, value_ptr| *value_ptr.cast::<u32>() += 1);
+        assert!(verify_checksum(base, size));
+        remove(base, id, generation);
+        assert!(verify_checksum(base, size));
+        clear(base);
+        assert!(verify_checksum(base, size));
+    }
+}
+
+#[test]
+fn test_generation_wraparound_skips_zero() {
+    use dense_slotmap_mem::{id_to_generation_ptr_pub, remove_with_overflow};
+
+    let capacity = 2u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        // Force the generation of a live id close to the wrap point
+        let (id, _) = allocate(base).unwrap();
+        *id_to_generation_ptr_pub(base).add(id as usize) = u16::MAX - 1;
+        let mut stale = Vec::new();
+
+        let old = (id, u16::MAX - 1);
+        assert_eq!(remove_with_overflow(base, old.0, old.1), (true, false));
+        stale.push(old);
+
+        let (reused_id, generation) = allocate(base).unwrap();
+        assert_eq!((reused_id, generation), (id, u16::MAX));
+
+        // Removing at u16::MAX wraps, and must skip generation 0
+        assert_eq!(remove_with_overflow(base, id, u16::MAX), (true, true));
+        stale.push((id, u16::MAX));
+        assert_eq!(remove_with_overflow(base, id, u16::MAX), (false, false));
+
+        let (reused_id, generation) = allocate(base).unwrap();
+        assert_eq!(reused_id, id);
+        assert_eq!(generation, 1, "Generation must restart at 1, never 0");
+        assert!(is_alive(base, id, generation));
+
+        // The ABA case the wrapped flag reports: the very first handle (id, 1) matches again
+        assert!(
+            is_alive(base, id, 1),
+            "After a wrap the generation 1 handle is alive again"
+        );
+
+        stale.push((id, 0));
+        for &(stale_id, stale_generation) in &stale {
+            assert!(
+                !is_alive(base, stale_id, stale_generation),
+                "Stale handle ({stale_id}, {stale_generation}) must not validate"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_clear_generation_wrap_skips_zero() {
+    use dense_slotmap_mem::id_to_generation_ptr_pub;
+
+    let capacity = 1u16;
+    let size = layout_size(capacity, 1);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, 1);
+        *id_to_generation_ptr_pub(base) = u16::MAX;
+        clear(base);
+        assert_eq!(allocate(base).unwrap(), (0, 1));
+    }
+}
+
+#[test]
+fn test_contains_id_and_current_generation() {
+    use dense_slotmap_mem::{contains_id, current_generation};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+        for id in 0..capacity {
+            assert!(!contains_id(base, id));
+            assert_eq!(current_generation(base, id), None);
+        }
+
+        let (a_id, a_gen) = allocate(base).unwrap();
+        let (b_id, b_gen) = allocate(base).unwrap();
+        assert!(contains_id(base, a_id));
+        assert!(contains_id(base, b_id));
+        assert_eq!(current_generation(base, a_id), Some(a_gen));
+
+        remove(base, a_id, a_gen);
+        assert!(!contains_id(base, a_id));
+        assert_eq!(current_generation(base, a_id), None);
+        assert!(contains_id(base, b_id));
+        assert_eq!(current_generation(base, b_id), Some(b_gen));
+
+        // Reallocated id reports its bumped generation
+        let (new_id, new_gen) = allocate(base).unwrap();
+        assert_eq!(new_id, a_id);
+        assert_eq!(current_generation(base, new_id), Some(new_gen));
+        assert!(is_alive(
+            base,
+            new_id,
+            current_generation(base, new_id).unwrap()
+        ));
+
+        assert!(!contains_id(base, capacity));
+        assert!(!contains_id(base, u16::MAX));
+    }
+}
+
+#[test]
+fn test_values_slice_sum() {
+    use dense_slotmap_mem::values_slice;
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in 1..=6u32 {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation));
+        }
+        remove(base, handles[2].0, handles[2].1); // drops 3
+
+        let (ptr, byte_len) = values_slice(base);
+        assert_eq!(ptr, base.add(values_offset(base)));
+        assert_eq!(byte_len, 5 * 4);
+
+        let values = std::slice::from_raw_parts(ptr as *const u32, byte_len / 4);
+        assert_eq!(values.iter().sum::<u32>(), 1 + 2 + 4 + 5 + 6);
+    }
+}
+
+#[test]
+fn test_copy_from_snapshot() {
+    use dense_slotmap_mem::{copy_from, get_value_ptr};
+
+    let capacity = 6u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut src_words = vec![0u32; size.div_ceil(4)];
+    let src = src_words.as_mut_ptr().cast::<u8>();
+    let mut dst_words = vec![0u32; size.div_ceil(4)];
+    let dst = dst_words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(src, capacity, element_size);
+        init(dst, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in [5u32, 6, 7, 8] {
+            let (id, generation) = allocate(src).unwrap();
+            insert(src, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation, value));
+        }
+        remove(src, handles[1].0, handles[1].1);
+        handles.remove(1);
+
+        copy_from(dst, src);
+        assert_eq!(src_words, dst_words);
+
+        // Mutate the original; the snapshot must not change
+        let (id, generation, _) = handles[0];
+        let new_value = 1000u32;
+        insert(src, id, generation, (&raw const new_value).cast::<u8>());
+        remove(src, handles[2].0, handles[2].1);
+        allocate(src).unwrap();
+
+        assert_eq!(element_count(dst), 3);
+        for &(id, generation, value) in &handles {
+            assert!(is_alive(dst, id, generation));
+            assert_eq!(
+                *(get_value_ptr(dst, id, generation).unwrap() as *const u32),
+                value
+            );
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_clone_into_vec() {
+    use dense_slotmap_mem::{clone_into_vec, get_value_ptr};
+
+    let capacity = 3u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        let value = 42u32;
+        let (id, generation) = allocate(base).unwrap();
+        insert(base, id, generation, (&raw const value).cast::<u8>());
+
+        let mut copy = clone_into_vec(base);
+        assert_eq!(copy, words);
+
+        let copy_base = copy.as_mut_ptr().cast::<u8>();
+        assert_eq!(
+            *(get_value_ptr(copy_base, id, generation).unwrap() as *const u32),
+            42
+        );
+    }
+}
+
+#[test]
+fn test_retain_even_values() {
+    use dense_slotmap_mem::{get_value_ptr, retain};
+
+    let capacity = 12u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in 0..10u32 {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation, value));
+        }
+
+        let mut visited = 0;
+        retain(base, |_, _, ptr| {
+            visited += 1;
+            (*(ptr as *const u32)).is_multiple_of(2)
+        });
+        assert_eq!(visited, 10, "Every element is examined exactly once");
+        assert_eq!(element_count(base), 5);
+        assert_eq!(
+            element_count(base) + dense_slotmap_mem::free_count(base),
+            capacity
+        );
+
+        for &(id, generation, value) in &handles {
+            if value % 2 == 0 {
+                assert!(is_alive(base, id, generation));
+                assert_eq!(
+                    *(get_value_ptr(base, id, generation).unwrap() as *const u32),
+                    value
+                );
+            } else {
+                assert!(!is_alive(base, id, generation));
+            }
+        }
+
+        // Dense region holds exactly the survivors
+        let mut survivors: Vec<u32> = (0..element_count(base) as usize)
+            .map(|i| *(base.add(values_offset(base) + i * 4) as *const u32))
+            .collect();
+        survivors.sort_unstable();
+        assert_eq!(survivors, [0, 2, 4, 6, 8]);
+
+        // Retaining nothing empties the map
+        retain(base, |_, _, _| false);
+        assert_eq!(element_count(base), 0);
+    }
+}
+
+#[test]
+fn test_debug_dump() {
+    use dense_slotmap_mem::debug_dump;
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+        let (a_id, a_gen) = allocate(base).unwrap(); // id 3
+        allocate(base).unwrap(); // id 2
+        remove(base, a_id, a_gen);
+
+        let mut dump = String::new();
+        debug_dump(base, &mut dump).unwrap();
+
+        assert!(dump.contains("capacity=4 len=1 free_top=3 element_size=4"));
+        assert!(dump.contains("id_to_index: [-, -, 0, -]"), "{dump}");
+        assert!(dump.contains("index_to_id: [2, -, -, -]"), "{dump}");
+        assert!(dump.contains("generation:  [1, 1, 1, 2]"), "{dump}");
+        assert!(dump.contains("free_stack:  [0, 1, 3]"), "{dump}");
+        assert!(dump.contains("invariant len + free_top == capacity: ok"));
+
+        // Break the invariant: the dump still works and flags it
+        *(base.add(2) as *mut u16) = 3;
+        let mut dump = String::new();
+        debug_dump(base, &mut dump).unwrap();
+        assert!(dump.contains("VIOLATED"));
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_dump_string() {
+    use dense_slotmap_mem::dump_string;
+
+    let capacity = 2u16;
+    let size = layout_size(capacity, 1);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, 1);
+        allocate(base).unwrap();
+        let dump = dump_string(base);
+        assert!(dump.contains("index_to_id: [1, -]"), "{dump}");
+        assert!(dump.ends_with("ok\n"));
+    }
+}
+
+#[test]
+fn test_allocate_batch() {
+    use dense_slotmap_mem::allocate_batch;
+
+    let capacity = 5u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = [(0u16, 0u16); 8];
+        assert_eq!(allocate_batch(base, 2, &mut handles), 2);
+        assert_eq!(element_count(base), 2);
+        assert_eq!(handles[..2], [(4, 1), (3, 1)]);
+
+        // Ask for more than is free: capped at the three remaining slots
+        let mut more = [(0u16, 0u16); 8];
+        let count = allocate_batch(base, 8, &mut more);
+        assert_eq!(count, 3);
+        assert_eq!(element_count(base), capacity);
+        assert!(allocate(base).is_none());
+
+        for &(id, generation) in handles[..2].iter().chain(&more[..count as usize]) {
+            assert!(is_alive(base, id, generation));
+        }
+
+        // Nothing left to hand out
+        assert_eq!(allocate_batch(base, 1, &mut more), 0);
+    }
+}
+
+#[test]
+fn test_remove_batch() {
+    use dense_slotmap_mem::{get_value_ptr, remove_batch};
+
+    let capacity = 6u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let handles: Vec<(u16, u16)> = (0..5).map(|_| allocate(base).unwrap()).collect();
+        for (i, &(id, generation)) in handles.iter().enumerate() {
+            let value = i as u32;
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+        }
+
+        // Already dead before the batch runs
+        let dead = handles[4];
+        assert!(remove(base, dead.0, dead.1));
+
+        // The first removal swaps the last element into index 0, so handles[3]
+        // moves before it is processed; the duplicate and dead handles are skipped
+        let batch = [handles[0], handles[3], handles[0], dead, handles[1]];
+        assert_eq!(remove_batch(base, &batch), 3);
+        assert_eq!(element_count(base), 1);
+
+        let (id, generation) = handles[2];
+        assert!(is_alive(base, id, generation));
+        assert_eq!(
+            *get_value_ptr(base, id, generation).unwrap().cast::<u32>(),
+            2
+        );
+        for &(id, generation) in &batch {
+            assert!(!is_alive(base, id, generation));
+        }
+    }
+}
+
+#[test]
+fn test_attach() {
+    use dense_slotmap_mem::{ValidationError, attach, required_len};
+
+    let capacity = 8u16;
+    let element_size = 12u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4) + 1];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        assert_eq!(required_len(base), size);
+    }
+
+    unsafe {
+        assert_eq!(attach(base, size), Ok(()));
+        assert_eq!(
+            attach(base, size - 1),
+            Err(ValidationError::BufferTooSmall {
+                required: size,
+                actual: size - 1,
+            })
+        );
+        assert_eq!(
+            attach(base, 4),
+            Err(ValidationError::BufferTooSmall {
+                required: 8,
+                actual: 4,
+            })
+        );
+        assert_eq!(
+            attach(base.add(2), size),
+            Err(ValidationError::BadAlignment)
+        );
+    }
+}
+
+#[test]
+fn test_get_disjoint_mut() {
+    use dense_slotmap_mem::{get_disjoint_mut, get_value_ptr};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        let a = allocate(base).unwrap();
+        let b = allocate(base).unwrap();
+
+        let [pa, pb] = get_disjoint_mut(base, [a, b]).unwrap();
+        assert_ne!(pa, pb);
+        pa.cast::<u32>().write(1);
+        pb.cast::<u32>().write(2);
+        assert_eq!(*get_value_ptr(base, b.0, b.1).unwrap().cast::<u32>(), 2);
+
+        // Same handle twice would alias
+        assert!(get_disjoint_mut(base, [a, a]).is_none());
+
+        // A stale handle anywhere fails the whole request
+        remove(base, b.0, b.1);
+        assert!(get_disjoint_mut(base, [a, b]).is_none());
+        assert!(get_disjoint_mut::<0>(base, []).is_some());
+    }
+}
+
+#[test]
+fn test_layout_offsets() {
+    use dense_slotmap_mem::layout_offsets;
+
+    let capacity = 4u16;
+    let element_size = 2u32;
+
+    // Same manual computation as test_layout_and_initialization
+    let header = 8;
+    let values_size = capacity as usize * element_size as usize;
+    let trailer_off = ((header + values_size) + 3) & !3;
+    let trailer_header = 16;
+    let arrays_off = ((trailer_off + trailer_header) + 3) & !3;
+    let array_size = capacity as usize * size_of::<u16>();
+
+    let offsets = layout_offsets(capacity, element_size);
+    assert_eq!(offsets.values_offset, header);
+    assert_eq!(offsets.trailer_offset, trailer_off);
+    assert_eq!(offsets.arrays_offset, arrays_off);
+    assert_eq!(offsets.id_to_index, arrays_off);
+    assert_eq!(offsets.index_to_id, arrays_off + array_size);
+    assert_eq!(offsets.generation, arrays_off + 2 * array_size);
+    assert_eq!(offsets.free_stack, arrays_off + 3 * array_size);
+    assert_eq!(
+        offsets.free_stack + array_size,
+        layout_size(capacity, element_size)
+    );
+
+    // Usable in const context
+    const OFFSETS: dense_slotmap_mem::LayoutOffsets = layout_offsets(16, 12);
+    assert_eq!(OFFSETS.trailer_offset, 8 + 16 * 12);
+}
+
+#[test]
+fn test_for_each() {
+    use dense_slotmap_mem::{for_each, get_value_ptr};
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in 1..=6u32 {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation));
+        }
+
+        let sum_via_for_each = |base: *mut u8| {
+            let mut sum = 0;
+            let mut seen = Vec::new();
+            for_each(base, |id, generation, value_ptr| {
+                assert_eq!(get_value_ptr(base, id, generation), Some(value_ptr));
+                sum += *value_ptr.cast::<u32>();
+                seen.push((id, generation));
+            });
+            (sum, seen)
+        };
+
+        let (sum, seen) = sum_via_for_each(base);
+        assert_eq!(sum, 21);
+        assert_eq!(seen, handles);
+
+        // Swap-removes reorder the dense array; the sum must follow
+        remove(base, handles[0].0, handles[0].1);
+        remove(base, handles[3].0, handles[3].1);
+
+        let mut manual = 0;
+        for &(id, generation) in &handles {
+            if let Some(ptr) = get_value_ptr(base, id, generation) {
+                manual += *ptr.cast::<u32>();
+            }
+        }
+        let (sum, seen) = sum_via_for_each(base);
+        assert_eq!(sum, manual);
+        assert_eq!(sum, 21 - 1 - 4);
+        assert_eq!(seen.len(), element_count(base) as usize);
+
+        // Writing values through the pointer is allowed
+        for_each(base, |_, _, value_ptr| *value_ptr.cast::<u32>() = 0);
+        assert_eq!(sum_via_for_each(base).0, 0);
+    }
+}
+
+#[test]
+fn test_clear_reset_generations() {
+    use dense_slotmap_mem::clear_reset_generations;
+
+    let capacity = 3u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        // Churn a slot so its generation climbs
+        for _ in 0..5 {
+            let (id, generation) = allocate(base).unwrap();
+            remove(base, id, generation);
+        }
+        let (old_id, old_generation) = allocate(base).unwrap();
+        assert!(old_generation > 1);
+
+        clear(base);
+        let (id, generation) = allocate(base).unwrap();
+        assert_eq!(id, old_id);
+        assert!(generation > old_generation);
+
+        clear_reset_generations(base);
+        assert_eq!(element_count(base), 0);
+        let (id, generation) = allocate(base).unwrap();
+        assert_eq!((id, generation), (old_id, 1));
+
+        let mut fresh = vec![0u8; size];
+        init(fresh.as_mut_ptr(), capacity, element_size);
+        clear_reset_generations(base);
+        assert_eq!(memory_buffer[8..], fresh[8..]);
+    }
+}
+
+#[test]
+fn test_init_checked() {
+    use dense_slotmap_mem::{InitError, SlotMapError, init_checked};
+
+    let capacity = 4u16;
+    let element_size = 8u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        assert_eq!(init_checked(base, capacity, element_size), Ok(()));
+        let (id, generation) = allocate(base).unwrap();
+
+        // Second init must not wipe the live element
+        assert_eq!(
+            init_checked(base, capacity, element_size),
+            Err(InitError::AlreadyInitialized)
+        );
+        assert!(is_alive(base, id, generation));
+
+        // `?` carries the failure into the crate-wide error type
+        let reinit = || -> Result<(), SlotMapError> {
+            init_checked(base, capacity, element_size)?;
+            Ok(())
+        };
+        assert_eq!(reinit(), Err(SlotMapError::AlreadyInitialized));
+
+        // A different shape over the same bytes is a deliberate reformat
+        assert_eq!(init_checked(base, capacity - 1, element_size), Ok(()));
+        assert_eq!(dense_slotmap_mem::capacity(base), capacity - 1);
+
+        // init is the forcing variant
+        init(base, capacity - 1, element_size);
+        assert_eq!(element_count(base), 0);
+    }
+
+    assert_eq!(
+        InitError::AlreadyInitialized.to_string(),
+        "buffer already holds an initialized slot map"
+    );
+}
+
+#[test]
+fn test_handle_from_value_ptr() {
+    use dense_slotmap_mem::{get_value_ptr, handle_from_value_ptr};
+
+    let capacity = 6u16;
+    let element_size = 12u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let handles: Vec<(u16, u16)> = (0..4).map(|_| allocate(base).unwrap()).collect();
+        // Reshuffle the dense order a bit and bump a generation
+        remove(base, handles[1].0, handles[1].1);
+        let reused = allocate(base).unwrap();
+
+        for &(id, generation) in [handles[0], handles[2], handles[3], reused].iter() {
+            let value_ptr = get_value_ptr(base, id, generation).unwrap();
+            assert_eq!(
+                handle_from_value_ptr(base, value_ptr),
+                Some((id, generation))
+            );
+            // Inside an element but not at its start
+            assert_eq!(handle_from_value_ptr(base, value_ptr.add(4)), None);
+        }
+
+        // Before the values region, and at the end of the live region
+        assert_eq!(handle_from_value_ptr(base, base), None);
+        let end = base.add(8 + element_count(base) as usize * element_size as usize);
+        assert_eq!(handle_from_value_ptr(base, end), None);
+    }
+}
+
+#[test]
+fn test_shrink_into() {
+    use dense_slotmap_mem::{Handle, get_value_ptr, shrink_into};
+
+    let capacity = 64u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut src_words = vec![0u32; size.div_ceil(4)];
+    let src = src_words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(src, capacity, element_size);
+
+        // Spike, then drop all but every tenth element
+        let mut handles = Vec::new();
+        for value in 0..capacity as u32 {
+            let (id, generation) = allocate(src).unwrap();
+            insert(src, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation, value));
+        }
+        for &(id, generation, value) in &handles {
+            if value % 10 != 0 {
+                remove(src, id, generation);
+            }
+        }
+        let live: Vec<_> = handles.iter().filter(|h| h.2 % 10 == 0).collect();
+        assert_eq!(element_count(src), live.len() as u16);
+
+        let dst_capacity = 8u16;
+        let mut dst_words = vec![0u32; layout_size(dst_capacity, element_size).div_ceil(4)];
+        let dst = dst_words.as_mut_ptr().cast::<u8>();
+
+        // Too small for the live elements
+        assert!(!shrink_into(dst, 6, src, &mut []));
+
+        let mut remap = vec![(Handle::NULL, Handle::NULL); live.len()];
+        assert!(shrink_into(dst, dst_capacity, src, &mut remap));
+        assert_eq!(element_count(dst), live.len() as u16);
+
+        for &&(id, generation, value) in &live {
+            let &(_, new_handle) = remap
+                .iter()
+                .find(|(old, _)| *old == Handle::new(id, generation))
+                .unwrap();
+            let (new_id, new_generation) = (new_handle.id(), new_handle.generation());
+            assert!(new_id < live.len() as u16);
+            assert_eq!(new_generation, 1);
+            let ptr = get_value_ptr(dst, new_id, new_generation).unwrap();
+            assert_eq!(*ptr.cast::<u32>(), value);
+        }
+
+        // Remaining ids can still be allocated
+        for _ in live.len()..dst_capacity as usize {
+            let (id, _) = allocate(dst).unwrap();
+            assert!(id >= live.len() as u16);
+        }
+        assert!(allocate(dst).is_none());
+    }
+}
+
+const STATIC_CAPACITY: u16 = 16;
+const STATIC_ELEMENT_SIZE: u32 = 12;
+const STATIC_SIZE: usize = 8 + 16 * 12 + 16 + 4 * 16 * 2;
+// Fails to compile if STATIC_SIZE is too small for the layout
+const _: usize =
+    dense_slotmap_mem::assert_layout_fits(STATIC_SIZE, STATIC_CAPACITY, STATIC_ELEMENT_SIZE);
+
+#[test]
+fn test_assert_layout_fits() {
+    use dense_slotmap_mem::assert_layout_fits;
+
+    assert_eq!(
+        STATIC_SIZE,
+        layout_size(STATIC_CAPACITY, STATIC_ELEMENT_SIZE)
+    );
+    assert_eq!(assert_layout_fits(STATIC_SIZE + 4, 16, 12), STATIC_SIZE + 4);
+
+    let too_small = std::panic::catch_unwind(|| assert_layout_fits(STATIC_SIZE - 1, 16, 12));
+    assert!(too_small.is_err());
+}
+
+#[test]
+fn test_values_base_ptr_and_stride() {
+    use dense_slotmap_mem::{get_value_ptr, stride, values_base_ptr, values_offset};
+
+    let capacity = 4u16;
+    let element_size = 6u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        let values = values_base_ptr(base);
+        assert_eq!(values, base.add(values_offset(base)));
+        assert_eq!(stride(element_size), 6);
+
+        allocate(base).unwrap();
+        let (id, generation) = allocate(base).unwrap();
+        assert_eq!(
+            get_value_ptr(base, id, generation),
+            Some(values.add(stride(element_size)))
+        );
+    }
+}
+
+#[test]
+fn test_insert_unchecked() {
+    use dense_slotmap_mem::{dense_index_of_id, insert_unchecked};
+
+    let capacity = 8u16;
+    let element_size = 8u32;
+    let size = layout_size(capacity, element_size);
+    let mut checked_words = vec![0u32; size.div_ceil(4)];
+    let mut unchecked_words = vec![0u32; size.div_ceil(4)];
+    let checked = checked_words.as_mut_ptr().cast::<u8>();
+    let unchecked = unchecked_words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(checked, capacity, element_size);
+        init(unchecked, capacity, element_size);
+
+        for value in 0..6u64 {
+            let (id, generation) = allocate(checked).unwrap();
+            assert_eq!(allocate(unchecked), Some((id, generation)));
+
+            let value = value * 0x0101_0101_0101;
+            insert(checked, id, generation, (&raw const value).cast::<u8>());
+            let index = dense_index_of_id(unchecked, id).unwrap();
+            insert_unchecked(unchecked, index, (&raw const value).cast::<u8>());
+        }
+    }
+
+    assert_eq!(checked_words, unchecked_words);
+}
+
+#[test]
+fn test_write_occupancy_bitmap() {
+    use dense_slotmap_mem::{contains_id, write_occupancy_bitmap};
+
+    let capacity = 13u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let handles: Vec<(u16, u16)> = (0..9).map(|_| allocate(base).unwrap()).collect();
+        remove(base, handles[2].0, handles[2].1);
+        remove(base, handles[7].0, handles[7].1);
+
+        // Stale bits and a sentinel byte past the bitmap
+        let mut bitmap = [0xFFu8; 3];
+        write_occupancy_bitmap(base, &mut bitmap[..2]);
+        assert_eq!(bitmap[2], 0xFF);
+
+        for id in 0..capacity {
+            let bit = bitmap[id as usize / 8] & (1 << (id % 8)) != 0;
+            assert_eq!(bit, contains_id(base, id), "id {id}");
+        }
+        assert_eq!(bitmap[1] >> 5, 0, "bits past capacity must be clear");
+        assert_eq!(
+            bitmap[..2].iter().map(|b| b.count_ones()).sum::<u32>(),
+            u32::from(element_count(base))
+        );
+    }
+}
+
+#[test]
+fn test_equal_contents() {
+    use dense_slotmap_mem::equal_contents;
+
+    let capacity = 6u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut a_words = vec![0u32; size.div_ceil(4)];
+    let mut b_words = vec![0u32; size.div_ceil(4)];
+    let a = a_words.as_mut_ptr().cast::<u8>();
+    let b = b_words.as_mut_ptr().cast::<u8>();
+
+    let push = |base: *mut u8, value: u32| unsafe {
+        let (id, generation) = allocate(base).unwrap();
+        insert(base, id, generation, (&raw const value).cast::<u8>());
+        (id, generation)
+    };
+
+    unsafe {
+        init(a, capacity, element_size);
+        init(b, capacity, element_size);
+
+        // a: 10, 20, 30 in order
+        push(a, 10);
+        push(a, 20);
+        push(a, 30);
+
+        // b: same values, reached through a removal that swaps 30 to the front
+        let (id, generation) = push(b, 99);
+        push(b, 20);
+        push(b, 10);
+        push(b, 30);
+        remove(b, id, generation);
+
+        assert!(equal_contents(a, b));
+        let values_len = 8 + 3 * element_size as usize;
+        assert_ne!(
+            std::slice::from_raw_parts(a, values_len)[8..],
+            std::slice::from_raw_parts(b, values_len)[8..],
+            "dense order differs, so a raw compare must fail"
+        );
+
+        // Same len, different multiset
+        push(a, 10);
+        push(b, 20);
+        assert!(!equal_contents(a, b));
+        push(a, 20);
+        push(b, 10);
+        assert!(equal_contents(a, b));
+
+        // Different len
+        push(a, 40);
+        assert!(!equal_contents(a, b));
+    }
+}
+
+#[test]
+fn test_truncate() {
+    use dense_slotmap_mem::{get_value_ptr, truncate};
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in 0..6u32 {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation));
+        }
+
+        assert!(!truncate(base, 7));
+        assert_eq!(element_count(base), 6);
+
+        assert!(truncate(base, 2));
+        assert_eq!(element_count(base), 2);
+
+        for (value, &(id, generation)) in handles.iter().enumerate() {
+            if value < 2 {
+                let ptr = get_value_ptr(base, id, generation).unwrap();
+                assert_eq!(*ptr.cast::<u32>(), value as u32);
+            } else {
+                assert!(!is_alive(base, id, generation));
+            }
+        }
+
+        // Dense index 2 is retired last, so its id is on top of the free stack
+        assert_eq!(allocate(base), Some((handles[2].0, handles[2].1 + 1)));
+        assert_eq!(element_count(base), 3);
+
+        assert!(truncate(base, 0));
+        assert_eq!(element_count(base), 0);
+        assert!(!is_alive(base, handles[0].0, handles[0].1));
+    }
+}
+
+#[test]
+fn test_peek_next_handle() {
+    use dense_slotmap_mem::peek_next_handle;
+
+    let capacity = 3u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        for cycle in 0..4 {
+            let mut handles = Vec::new();
+            for _ in 0..capacity {
+                let predicted = peek_next_handle(base);
+                assert_eq!(peek_next_handle(base), predicted, "peek must not mutate");
+                let allocated = allocate(base);
+                assert_eq!(predicted, allocated, "cycle {cycle}");
+                handles.push(allocated.unwrap());
+            }
+            assert_eq!(peek_next_handle(base), None);
+
+            // Remove in a cycle-dependent order to vary the free stack
+            handles.rotate_left(cycle % capacity as usize);
+            for (id, generation) in handles {
+                remove(base, id, generation);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_invalidate() {
+    use dense_slotmap_mem::{dense_index_of_id, get_value_ptr, invalidate};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        allocate(base).unwrap();
+        let (id, generation) = allocate(base).unwrap();
+        let value = 1234u32;
+        insert(base, id, generation, (&raw const value).cast::<u8>());
+        let index = dense_index_of_id(base, id);
+
+        let new_generation = invalidate(base, id, generation).unwrap();
+        assert_eq!(new_generation, generation + 1);
+        assert!(!is_alive(base, id, generation));
+        assert!(is_alive(base, id, new_generation));
+
+        // Data and position stay put
+        assert_eq!(dense_index_of_id(base, id), index);
+        assert_eq!(element_count(base), 2);
+        let ptr = get_value_ptr(base, id, new_generation).unwrap();
+        assert_eq!(*ptr.cast::<u32>(), value);
+
+        // The old handle can not be invalidated again
+        assert_eq!(invalidate(base, id, generation), None);
+
+        // Wrap skips generation 0
+        let mut current = new_generation;
+        while current != u16::MAX {
+            current = invalidate(base, id, current).unwrap();
+        }
+        assert_eq!(invalidate(base, id, current), Some(1));
+    }
+}
+
+#[test]
+fn test_zero_size_elements() {
+    use dense_slotmap_mem::{free_count, get_value_ptr, try_validate};
+
+    let capacity = 4u16;
+    let size = layout_size(capacity, 0);
+    assert_eq!(size, 8 + 16 + 4 * 4 * 2);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, 0);
+        assert_eq!(try_validate(base, size), Ok(()));
+
+        let handles: Vec<(u16, u16)> = (0..capacity).map(|_| allocate(base).unwrap()).collect();
+        assert!(allocate(base).is_none());
+        assert_eq!(free_count(base) + element_count(base), capacity);
+
+        // Value access is valid but zero-length
+        let (id, generation) = handles[1];
+        assert!(insert(base, id, generation, std::ptr::null()));
+        assert_eq!(get_value_ptr(base, id, generation), Some(base.add(8)));
+
+        // Swap-remove of nothing still keeps the bookkeeping right
+        assert!(remove(base, id, generation));
+        assert!(!is_alive(base, id, generation));
+        for &(other_id, other_generation) in handles.iter().filter(|h| h.0 != id) {
+            assert!(is_alive(base, other_id, other_generation));
+        }
+        assert_eq!(free_count(base) + element_count(base), capacity);
+
+        let (reused_id, reused_generation) = allocate(base).unwrap();
+        assert_eq!((reused_id, reused_generation), (id, generation + 1));
+        assert_eq!(try_validate(base, size), Ok(()));
+    }
+}
+
+#[test]
+fn test_iter_by_id() {
+    use dense_slotmap_mem::{iter, iter_by_id};
+
+    let capacity = 6u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for _ in 0..5 {
+            let (id, generation) = allocate(base).unwrap();
+            let value = u32::from(id) * 100;
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation));
+        }
+
+        // Remove a middle element and allocate again: dense order is now shuffled
+        remove(base, handles[2].0, handles[2].1);
+        let (id, generation) = allocate(base).unwrap();
+        let value = u32::from(id) * 100;
+        insert(base, id, generation, (&raw const value).cast::<u8>());
+
+        let dense_ids: Vec<u16> = iter(base).map(|(id, _, _)| id).collect();
+        let stable = iter_by_id(base);
+        assert_eq!(stable.len(), element_count(base) as usize);
+        let stable: Vec<_> = stable.collect();
+        let stable_ids: Vec<u16> = stable.iter().map(|&(id, _, _)| id).collect();
+
+        assert!(stable_ids.is_sorted());
+        assert_ne!(dense_ids, stable_ids);
+        let mut sorted_dense = dense_ids.clone();
+        sorted_dense.sort_unstable();
+        assert_eq!(sorted_dense, stable_ids);
+
+        for &(id, generation, value_ptr) in &stable {
+            assert!(is_alive(base, id, generation));
+            assert_eq!(*value_ptr.cast::<u32>(), u32::from(id) * 100);
+        }
+    }
+}
+
+#[test]
+fn test_merge_into() {
+    use dense_slotmap_mem::{Handle, get_value_ptr, merge_into};
+
+    let element_size = 4u32;
+    let small = 3u16;
+    let mut worker_words = [
+        vec![0u32; layout_size(small, element_size).div_ceil(4)],
+        vec![0u32; layout_size(small, element_size).div_ceil(4)],
+    ];
+    let master_capacity = 8u16;
+    let mut master_words = vec![0u32; layout_size(master_capacity, element_size).div_ceil(4)];
+    let master = master_words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(master, master_capacity, element_size);
+
+        let mut remaps = Vec::new();
+        for (worker, words) in worker_words.iter_mut().enumerate() {
+            let base = words.as_mut_ptr().cast::<u8>();
+            init(base, small, element_size);
+            for i in 0..small as u32 {
+                let value = worker as u32 * 10 + i;
+                let (id, generation) = allocate(base).unwrap();
+                insert(base, id, generation, (&raw const value).cast::<u8>());
+            }
+
+            let mut remap = [(Handle::NULL, Handle::NULL); 3];
+            assert_eq!(merge_into(master, base, &mut remap), small);
+            remaps.push((base, remap));
+        }
+        assert_eq!(element_count(master), 6);
+
+        // Both workers used the same ids, but the master handles are all distinct
+        let mut master_ids: Vec<u16> = remaps
+            .iter()
+            .flat_map(|(_, remap)| remap.iter().map(|(_, new)| new.id()))
+            .collect();
+        master_ids.sort_unstable();
+        master_ids.dedup();
+        assert_eq!(master_ids.len(), 6);
+
+        for (base, remap) in &remaps {
+            for (old, new) in remap {
+                let old_value = get_value_ptr(*base, old.id(), old.generation()).unwrap();
+                let new_value = get_value_ptr(master, new.id(), new.generation()).unwrap();
+                assert_eq!(*old_value.cast::<u32>(), *new_value.cast::<u32>());
+            }
+        }
+
+        // Only two slots left: the merge stops early
+        let (base, _) = remaps[0];
+        assert_eq!(merge_into(master, base, &mut []), 2);
+        assert_eq!(element_count(master), master_capacity);
+    }
+}
+
+#[test]
+fn test_read_write_value() {
+    use dense_slotmap_mem::{read_value, write_value};
+
+    let capacity = 2u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+        let (id, generation) = allocate(base).unwrap();
+
+        assert!(write_value(base, id, generation, [1, 2, 3, 4]));
+        assert_eq!(read_value::<4>(base, id, generation), Some([1, 2, 3, 4]));
+
+        remove(base, id, generation);
+        assert_eq!(read_value::<4>(base, id, generation), None);
+        assert!(!write_value(base, id, generation, [5, 6, 7, 8]));
+    }
+}
+
+#[test]
+// Debug builds catch the corruption up front, in debug_validate_slotmap
+#[cfg_attr(debug_assertions, should_panic(expected = "is on the free stack"))]
+fn test_remove_on_corrupted_empty_map() {
+    use dense_slotmap_mem::{layout_offsets, remove_batch};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        let (id, generation) = allocate(base).unwrap();
+
+        // Zero len (keeping len + free_top == capacity) while the id mappings still
+        // say the handle is alive
+        *base.add(2).cast::<u16>() = 0;
+        let free_top = base.add(layout_offsets(capacity, element_size).trailer_offset + 8);
+        *free_top.cast::<u16>() = capacity;
+        assert!(is_alive(base, id, generation));
+
+        assert!(!remove(base, id, generation));
+        assert_eq!(remove_batch(base, &[(id, generation)]), 0);
+        assert_eq!(element_count(base), 0);
+    }
+}
+
+#[test]
+fn test_capacity_for_budget() {
+    use dense_slotmap_mem::capacity_for_budget;
+
+    for element_size in [0u32, 1, 3, 4, 12, 100] {
+        for budget in [64usize, 100, 1000, 4096, 64 * 1024, 1 << 20] {
+            let capacity = capacity_for_budget(budget, element_size);
+            if capacity > 0 {
+                assert!(layout_size(capacity, element_size) <= budget);
+            }
+            if capacity < u16::MAX {
+                assert!(
+                    layout_size(capacity + 1, element_size) > budget,
+                    "budget {budget}, element_size {element_size}: {capacity} + 1 also fits"
+                );
+            }
+        }
+    }
+
+    // Too small for even one element
+    assert_eq!(capacity_for_budget(16, 4), 0);
+    // Huge budgets are capped
+    assert_eq!(capacity_for_budget(usize::MAX, 4), u16::MAX);
+}
+
+#[test]
+fn test_generation_statistics() {
+    use dense_slotmap_mem::{current_generation, generation_histogram, max_generation};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+        assert_eq!(max_generation(base), 1);
+
+        // Churn a single id
+        let mut hot = allocate(base).unwrap();
+        for _ in 0..40_000 {
+            remove(base, hot.0, hot.1);
+            hot = allocate(base).unwrap();
+        }
+        assert_eq!(max_generation(base), 40_001);
+        assert_eq!(current_generation(base, hot.0), Some(40_001));
+
+        let others: Vec<(u16, u16)> = (1..capacity).map(|_| allocate(base).unwrap()).collect();
+        for &(id, generation) in &others {
+            assert_ne!(id, hot.0);
+            assert_eq!(generation, 1);
+        }
+
+        // Two buckets: 0..32768 and 32768..65536
+        let mut histogram = [7u32; 2];
+        generation_histogram(base, &mut histogram);
+        assert_eq!(histogram, [3, 1]);
+
+        generation_histogram(base, &mut []);
+    }
+}
+
+#[test]
+fn test_reinit_element_size() {
+    use dense_slotmap_mem::{read_value, reinit_element_size, write_value};
+
+    let buffer_len = 256;
+    let mut words = vec![0u32; buffer_len / 4];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        let small = reinit_element_size(base, buffer_len, 4).unwrap();
+        assert!(layout_size(small, 4) <= buffer_len);
+        let (id, generation) = allocate(base).unwrap();
+        assert!(write_value(base, id, generation, [1u8; 4]));
+        assert_eq!(read_value::<4>(base, id, generation), Some([1u8; 4]));
+
+        let large = reinit_element_size(base, buffer_len, 16).unwrap();
+        assert!(large < small);
+        assert!(layout_size(large, 16) <= buffer_len);
+        assert_eq!(element_count(base), 0);
+
+        let mut handles = Vec::new();
+        for i in 0..large {
+            let (id, generation) = allocate(base).unwrap();
+            assert!(write_value(base, id, generation, [i as u8; 16]));
+            handles.push((id, generation));
+        }
+        assert!(allocate(base).is_none());
+        for (i, &(id, generation)) in handles.iter().enumerate() {
+            assert_eq!(read_value::<16>(base, id, generation), Some([i as u8; 16]));
+        }
+
+        // Does not fit even one element: the buffer is left alone
+        assert_eq!(reinit_element_size(base, buffer_len, 1024), None);
+        assert_eq!(element_count(base), large);
+    }
+}
+
+#[test]
+fn test_drain() {
+    use dense_slotmap_mem::{drain, free_count, try_validate};
+
+    let capacity = 8u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handles = Vec::new();
+        for value in 0..5u32 {
+            let (id, generation) = allocate(base).unwrap();
+            insert(base, id, generation, (&raw const value).cast::<u8>());
+            handles.push((id, generation, value));
+        }
+
+        let mut drained = Vec::new();
+        drain(base, |id, generation, value_ptr| {
+            drained.push((id, generation, *value_ptr.cast::<u32>()));
+        });
+
+        // Taken from the tail, each element exactly once
+        handles.reverse();
+        assert_eq!(drained, handles);
+
+        assert_eq!(element_count(base), 0);
+        assert_eq!(free_count(base), capacity);
+        assert_eq!(try_validate(base, size), Ok(()));
+        for &(id, generation, _) in &handles {
+            assert!(!is_alive(base, id, generation));
+        }
+
+        // Draining an empty map calls nothing
+        drain(base, |_, _, _| panic!("map is empty"));
+    }
+}
+
+#[test]
+fn test_are_alive() {
+    use dense_slotmap_mem::are_alive;
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let live = allocate(base).unwrap();
+        let dead = allocate(base).unwrap();
+        remove(base, dead.0, dead.1);
+        let never_allocated = (0u16, 1u16);
+
+        let handles = [
+            live,
+            dead,
+            (live.0, live.1 + 1),
+            (capacity, 1),
+            (u16::MAX, 1),
+            never_allocated,
+        ];
+        let mut out = [true; 6];
+        are_alive(base, &handles, &mut out);
+        assert_eq!(out, [true, false, false, false, false, false]);
+    }
+}
+
+#[test]
+fn test_mem_layout() {
+    use dense_slotmap_mem::mem_layout;
+
+    for (capacity, element_size) in [(1u16, 0u32), (4, 2), (100, 12), (u16::MAX, 64)] {
+        let layout = mem_layout(capacity, element_size);
+        assert_eq!(layout.size(), layout_size(capacity, element_size));
+        assert_eq!(layout.align(), alignment());
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test_boxed() {
+    use dense_slotmap_mem::{boxed, try_validate};
+
+    let capacity = 10u16;
+    let element_size = 6u32;
+    let mut buffer = boxed(capacity, element_size);
+    let size = layout_size(capacity, element_size);
+    assert!(buffer.len() * 4 >= size);
+
+    let base = buffer.as_mut_ptr().cast::<u8>();
+    assert_eq!(try_validate(base, size), Ok(()));
+    unsafe {
+        let (id, generation) = allocate(base).unwrap();
+        assert!(is_alive(base, id, generation));
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "is on the free stack")]
+fn test_debug_validate_detects_id_in_free_stack_and_dense_set() {
+    use dense_slotmap_mem::{debug_validate_slotmap, layout_offsets};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        let (id, _) = allocate(base).unwrap();
+        debug_validate_slotmap(base);
+
+        // Overwrite a free stack entry with the occupied id, as if it was freed twice
+        let free_stack = base
+            .add(layout_offsets(capacity, element_size).free_stack)
+            .cast::<u16>();
+        *free_stack = id;
+
+        debug_validate_slotmap(base);
+    }
+}
+
+#[test]
+fn test_allocate_specific() {
+    use dense_slotmap_mem::{allocate_specific, iter, peek_next_handle};
+
+    let capacity = 5u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut a_buffer = vec![0u8; size];
+    let mut b_buffer = vec![0u8; size];
+    let a = a_buffer.as_mut_ptr();
+    let b = b_buffer.as_mut_ptr();
+
+    unsafe {
+        init(a, capacity, element_size);
+        init(b, capacity, element_size);
+
+        // Same generations, but the free stacks end up in different orders
+        let handles: Vec<(u16, u16)> = (0..3).map(|_| allocate(a).unwrap()).collect();
+        for &(id, generation) in &handles {
+            assert_eq!(allocate(b), Some((id, generation)));
+        }
+        for &(id, generation) in &handles {
+            remove(a, id, generation);
+        }
+        for &(id, generation) in handles.iter().rev() {
+            remove(b, id, generation);
+        }
+        assert_ne!(peek_next_handle(a), peek_next_handle(b));
+
+        for wanted in [2u16, 0, 4] {
+            let generation_a = allocate_specific(a, wanted).unwrap();
+            let generation_b = allocate_specific(b, wanted).unwrap();
+            assert_eq!(generation_a, generation_b);
+            assert!(is_alive(a, wanted, generation_a));
+        }
+
+        let dense_a: Vec<_> = iter(a)
+            .map(|(id, generation, _)| (id, generation))
+            .collect();
+        let dense_b: Vec<_> = iter(b)
+            .map(|(id, generation, _)| (id, generation))
+            .collect();
+        assert_eq!(dense_a, dense_b);
+        assert_eq!(dense_a.iter().map(|h| h.0).collect::<Vec<_>>(), [2, 0, 4]);
+
+        // Occupied and out of range ids are refused
+        assert_eq!(allocate_specific(a, 2), None);
+        assert_eq!(allocate_specific(a, capacity), None);
+
+        // The remaining free ids are still handed out by allocate
+        let mut rest: Vec<u16> = (0..2).map(|_| allocate(a).unwrap().0).collect();
+        rest.sort_unstable();
+        assert_eq!(rest, [1, 3]);
+        assert!(allocate(a).is_none());
+    }
+}
+
+#[test]
+fn test_compact_generations() {
+    use dense_slotmap_mem::{compact_generations, iter, max_generation};
+
+    let capacity = 4u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut memory_buffer = vec![0u8; size];
+    let base = memory_buffer.as_mut_ptr();
+
+    unsafe {
+        init(base, capacity, element_size);
+
+        let mut handle = allocate(base).unwrap();
+        for _ in 0..1000 {
+            remove(base, handle.0, handle.1);
+            handle = allocate(base).unwrap();
+        }
+        let value = 77u32;
+        insert(base, handle.0, handle.1, (&raw const value).cast::<u8>());
+        let other = allocate(base).unwrap();
+        assert_eq!(max_generation(base), 1001);
+
+        compact_generations(base);
+        assert_eq!(max_generation(base), 1);
+        assert_eq!(element_count(base), 2);
+        assert!(!is_alive(base, handle.0, handle.1));
+
+        // Fresh handles still reach the same values
+        let fresh: Vec<_> = iter(base).collect();
+        assert_eq!(fresh.len(), 2);
+        assert_eq!(fresh[0].0, handle.0);
+        assert_eq!(fresh[0].1, 1);
+        assert_eq!(*fresh[0].2.cast::<u32>(), value);
+        assert!(is_alive(base, other.0, 1));
+    }
+}
+
+#[test]
+fn test_describe() {
+    use dense_slotmap_mem::describe;
+
+    for (capacity, element_size) in [(4u16, 2u32), (7, 5), (32, 16)] {
+        let size = layout_size(capacity, element_size);
+        let mut words = vec![0u32; size.div_ceil(4)];
+        let base = words.as_mut_ptr().cast::<u8>();
+
+        unsafe {
+            init(base, capacity, element_size);
+            allocate(base).unwrap();
+            allocate(base).unwrap();
+
+            let description = describe(base);
+            assert_eq!(description.capacity, capacity);
+            assert_eq!(description.len, 2);
+            assert_eq!(description.element_size, element_size);
+            assert_eq!(description.free_top, capacity - 2);
+
+            let trailer_off = (8 + capacity as usize * element_size as usize + 3) & !3;
+            let arrays_off = (trailer_off + 16 + 3) & !3;
+            let array_size = capacity as usize * 2;
+            let offsets = description.offsets;
+            assert_eq!(offsets.values_offset, 8);
+            assert_eq!(offsets.trailer_offset, trailer_off);
+            assert_eq!(offsets.arrays_offset, arrays_off);
+            assert_eq!(offsets.id_to_index, arrays_off);
+            assert_eq!(offsets.index_to_id, arrays_off + array_size);
+            assert_eq!(offsets.generation, arrays_off + 2 * array_size);
+            assert_eq!(offsets.free_stack, arrays_off + 3 * array_size);
+
+            // The trailer magic really is where the description says
+            assert_eq!(*base.add(offsets.trailer_offset).cast::<u32>(), 0x5356_4532);
+        }
+    }
+}
+
+#[test]
+fn test_update() {
+    use dense_slotmap_mem::{read_value, update};
+
+    let capacity = 2u16;
+    let element_size = 4u32;
+    let size = layout_size(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init(base, capacity, element_size);
+        let (id, generation) = allocate(base).unwrap();
+        let value = 41u32;
+        insert(base, id, generation, (&raw const value).cast::<u8>());
+
+        assert!(update(base, id, generation, |ptr| {
+            *ptr.cast::<u32>() += 1;
+        }));
+        assert_eq!(
+            read_value::<4>(base, id, generation),
+            Some(42u32.to_ne_bytes())
+        );
+
+        let (other_id, other_generation) = allocate(base).unwrap();
+        remove(base, other_id, other_generation);
+        let before = words.clone();
+        let base = words.as_mut_ptr().cast::<u8>();
+        let mut called = false;
+        assert!(!update(base, other_id, other_generation, |_| called = true));
+        assert!(!called);
+        assert_eq!(words, before);
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+use dense_slotmap_mem::SlotMapError;
+use dense_slotmap_mem::layout_size;
+use dense_slotmap_mem::typed::SlotMap;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Body {
+    position: [f32; 2],
+    mass: f32,
+}
+
+/// 4-byte aligned byte storage, as required by the slot map layout
+fn aligned_buffer(size: usize) -> Vec<u32> {
+    vec![0u32; size.div_ceil(4)]
+}
+
+fn as_bytes(words: &mut [u32]) -> &mut [u8] {
+    let len = words.len() * 4;
+    unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr().cast::<u8>(), len) }
+}
+
+#[test]
+fn test_typed_insert_get_remove() {
+    let capacity = 4u16;
+    let mut words = aligned_buffer(layout_size(capacity, size_of::<Body>() as u32));
+    let mut map = SlotMap::<Body>::new(as_bytes(&mut words), capacity).unwrap();
+    assert!(map.is_empty());
+    assert_eq!(map.capacity(), capacity);
+
+    let a = map
+        .insert(Body {
+            position: [1.0, 2.0],
+            mass: 3.0,
+        })
+        .unwrap();
+    let b = map
+        .insert(Body {
+            position: [4.0, 5.0],
+            mass: 6.0,
+        })
+        .unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(a).unwrap().mass, 3.0);
+
+    map.get_mut(b).unwrap().mass = 60.0;
+    assert_eq!(map.get(b).unwrap().mass, 60.0);
+
+    // Removing `a` swaps `b` into its dense slot; the handle for `b` must still resolve
+    let removed = map.remove(a).unwrap();
+    assert_eq!(removed.position, [1.0, 2.0]);
+    assert!(!map.contains(a));
+    assert!(map.get(a).is_none());
+    assert!(map.remove(a).is_none());
+    assert_eq!(map.get(b).unwrap().position, [4.0, 5.0]);
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_typed_full() {
+    let capacity = 2u16;
+    let mut words = aligned_buffer(layout_size(capacity, 4));
+    let mut map = SlotMap::<u32>::new(as_bytes(&mut words), capacity).unwrap();
+
+    map.insert(1).unwrap();
+    map.insert(2).unwrap();
+    assert_eq!(map.insert(3), Err(SlotMapError::Full));
+}
+
+#[test]
+fn test_typed_constructor_checks() {
+    let mut words = aligned_buffer(layout_size(4, 8));
+
+    assert_eq!(
+        SlotMap::<u64>::new(as_bytes(&mut words), 4).err(),
+        Some(SlotMapError::Misaligned { required: 8 })
+    );
+    assert_eq!(
+        SlotMap::<u32>::new(as_bytes(&mut words), 0).err(),
+        Some(SlotMapError::ZeroCapacity)
+    );
+
+    let required = layout_size(100, 4);
+    let actual = words.len() * 4;
+    assert_eq!(
+        SlotMap::<u32>::new(as_bytes(&mut words), 100).err(),
+        Some(SlotMapError::BufferTooSmall { required, actual })
+    );
+
+    let bytes = as_bytes(&mut words);
+    assert_eq!(
+        SlotMap::<u32>::new(&mut bytes[1..], 2).err(),
+        Some(SlotMapError::Misaligned { required: 4 })
+    );
+}
+
+#[test]
+fn test_typed_from_words() {
+    let capacity = 4u16;
+    let mut words = aligned_buffer(layout_size(capacity, 4));
+    let mut map = SlotMap::<u32>::from_words(&mut words, capacity).unwrap();
+    let handle = map.insert(42).unwrap();
+    assert_eq!(map.get(handle), Some(&42));
+
+    let mut short = aligned_buffer(layout_size(capacity, 4) - 4);
+    assert!(matches!(
+        SlotMap::<u32>::from_words(&mut short, capacity),
+        Err(SlotMapError::BufferTooSmall { .. })
+    ));
+}
+
+#[test]
+fn test_typed_from_initialized() {
+    let capacity = 4u16;
+    let mut words = aligned_buffer(layout_size(capacity, 4));
+
+    let handle = {
+        let mut map = SlotMap::<u32>::new(as_bytes(&mut words), capacity).unwrap();
+        map.insert(1234).unwrap()
+    };
+
+    let map = unsafe { SlotMap::<u32>::from_initialized(as_bytes(&mut words)) }.unwrap();
+    assert_eq!(map.get(handle), Some(&1234));
+
+    assert_eq!(
+        unsafe { SlotMap::<u16>::from_initialized(as_bytes(&mut words)) }.err(),
+        Some(SlotMapError::ElementSizeMismatch {
+            expected: 2,
+            actual: 4
+        })
+    );
+
+    let mut zeroed = aligned_buffer(layout_size(capacity, 4));
+    assert_eq!(
+        unsafe { SlotMap::<u32>::from_initialized(as_bytes(&mut zeroed)) }.err(),
+        Some(SlotMapError::ZeroCapacity)
+    );
+
+    // Corrupt the trailer magic
+    let trailer_words = (8 + capacity as usize * 4) / 4;
+    words[trailer_words] = 0xDEAD_BEEF;
+    assert!(matches!(
+        unsafe { SlotMap::<u32>::from_initialized(as_bytes(&mut words)) },
+        Err(SlotMapError::Corrupt { .. })
+    ));
+}
+
+#[test]
+fn test_typed_values_slice() {
+    let capacity = 8u16;
+    let mut words = aligned_buffer(layout_size(capacity, 4));
+    let mut map = SlotMap::<u32>::new(as_bytes(&mut words), capacity).unwrap();
+
+    let handles: Vec<_> = (1..=5).map(|value| map.insert(value).unwrap()).collect();
+    map.remove(handles[0]);
+    assert_eq!(map.values().iter().sum::<u32>(), 2 + 3 + 4 + 5);
+
+    for value in map.values_mut() {
+        *value *= 10;
+    }
+    assert_eq!(map.get(handles[4]), Some(&50));
+    assert_eq!(map.values().len(), 4);
+}
+
+#[test]
+fn test_zero_size_type() {
+    let capacity = 3u16;
+    let mut storage = aligned_buffer(layout_size(capacity, 0));
+    let mut map = SlotMap::<()>::new(as_bytes(&mut storage), capacity).unwrap();
+
+    let a = map.insert(()).unwrap();
+    let b = map.insert(()).unwrap();
+    assert_eq!(map.get(a), Some(&()));
+    assert_eq!(map.remove(a), Some(()));
+    assert!(!map.contains(a));
+    assert!(map.contains(b));
+    assert_eq!(map.len(), 1);
+}
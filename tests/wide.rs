@@ -0,0 +1,64 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/dense-slotmap-mem
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+
+use dense_slotmap_mem::wide::{
+    allocate32, capacity32, element_count32, get_value_ptr32, init32, insert32, is_alive32,
+    layout_size32, remove32,
+};
+
+#[test]
+fn test_wide_large_capacity() {
+    let capacity = 200_000u32;
+    let element_size = 4u32;
+    let size = layout_size32(capacity, element_size);
+    let mut words = vec![0u32; size.div_ceil(4)];
+    let base = words.as_mut_ptr().cast::<u8>();
+
+    unsafe {
+        init32(base, capacity, element_size);
+        assert_eq!(capacity32(base), capacity);
+        assert_eq!(element_count32(base), 0);
+
+        let mut handles = Vec::with_capacity(capacity as usize);
+        for value in 0..capacity {
+            let (id, generation) = allocate32(base).expect("should allocate");
+            assert_eq!(generation, 1);
+            assert!(insert32(
+                base,
+                id,
+                generation,
+                (&raw const value).cast::<u8>()
+            ));
+            handles.push((id, generation, value));
+        }
+        assert_eq!(element_count32(base), capacity);
+        assert!(allocate32(base).is_none(), "Should be full");
+
+        // Remove every third element, including ids beyond the u16 range
+        for &(id, generation, _) in handles.iter().step_by(3) {
+            assert!(remove32(base, id, generation));
+            assert!(!is_alive32(base, id, generation));
+        }
+        let removed = handles.iter().step_by(3).count() as u32;
+        assert_eq!(element_count32(base), capacity - removed);
+
+        for (i, &(id, generation, value)) in handles.iter().enumerate() {
+            if i % 3 == 0 {
+                assert!(get_value_ptr32(base, id, generation).is_none());
+            } else {
+                let stored = *(get_value_ptr32(base, id, generation).unwrap() as *const u32);
+                assert_eq!(stored, value);
+            }
+        }
+
+        // LIFO reuse with bumped generation
+        let (last_removed_id, last_removed_generation, _) =
+            *handles.iter().step_by(3).next_back().unwrap();
+        let (id, generation) = allocate32(base).unwrap();
+        assert_eq!(id, last_removed_id);
+        assert_eq!(generation, last_removed_generation + 1);
+        assert!(!is_alive32(base, capacity, 1), "Out of range id");
+    }
+}